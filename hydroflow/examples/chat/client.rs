@@ -24,14 +24,7 @@ pub(crate) async fn run_client(opts: Opts) {
     let messages_send = df.wrap_output(messages_send);
 
     // setup stdio input handler
-    let text_out = {
-        use futures::stream::StreamExt;
-        use tokio::io::AsyncBufReadExt;
-        let reader = tokio::io::BufReader::new(tokio::io::stdin());
-        let lines = tokio_stream::wrappers::LinesStream::new(reader.lines())
-            .map(|result| Some(result.expect("Failed to read stdin as UTF-8.")));
-        df.add_input_from_stream::<_, _, VecHandoff<String>, _>("stdin", lines)
-    };
+    let text_out = df.add_stdin_lines();
 
     // format addresses
     let addr = format!("localhost:{}", opts.port);
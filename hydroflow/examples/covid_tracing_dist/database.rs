@@ -1,9 +1,10 @@
-use crate::{people, Decode, Encode, Opts, CONTACTS_ADDR, DIAGNOSES_ADDR};
+use crate::{people, Opts, CONTACTS_ADDR, DIAGNOSES_ADDR};
 
 use std::time::Duration;
 
 use hydroflow::compiled::{pull::SymmetricHashJoin, IteratorToPusherator, PusheratorBuild};
 use hydroflow::lang::collections::Iter;
+use hydroflow::scheduled::net::codec::{Decode, Encode, JsonCodec};
 use hydroflow::scheduled::{handoff::VecHandoff, net::Message};
 use hydroflow::tokio::net::TcpListener;
 use hydroflow::{
@@ -46,9 +47,8 @@ pub(crate) async fn run_database(opts: Opts) {
         notifs,
         |_ctx, recv, send| {
             for message in recv.take_inner().into_iter() {
-                send.give(Iter(
-                    <Vec<(String, usize)>>::decode(message.batch).into_iter(),
-                ));
+                let batch: Vec<(String, usize)> = JsonCodec.decode(message.batch).unwrap();
+                send.give(Iter(batch.into_iter()));
             }
         },
     );
@@ -105,11 +105,10 @@ pub(crate) async fn run_database(opts: Opts) {
         contacts_recv,
         encode_contacts_out,
         |_ctx, recv, send| {
-            let mut buf = Vec::new();
-            recv.take_inner().encode(&mut buf);
+            let batch = JsonCodec.encode(&recv.take_inner()).unwrap();
             send.give(Some(Message {
                 address: CONTACTS_ADDR,
-                batch: buf.into(),
+                batch,
             }));
         },
     );
@@ -119,11 +118,10 @@ pub(crate) async fn run_database(opts: Opts) {
         diagnosed_recv,
         encode_diagnoses_out,
         |_ctx, recv, send| {
-            let mut buf = Vec::new();
-            recv.take_inner().encode(&mut buf);
+            let batch = JsonCodec.encode(&recv.take_inner()).unwrap();
             send.give(Some(Message {
                 address: DIAGNOSES_ADDR,
-                batch: buf.into(),
+                batch,
             }));
         },
     );
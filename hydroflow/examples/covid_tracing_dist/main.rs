@@ -1,7 +1,6 @@
 use clap::{ArgEnum, Parser};
 use database::run_database;
 use hydroflow::tokio;
-use serde::{de::DeserializeOwned, Serialize};
 use tracker::run_tracker;
 
 mod database;
@@ -38,36 +37,6 @@ struct Opts {
     addr: String,
 }
 
-// TODO(justin): this trait kind of sucks but it's a placeholder.
-trait Encode {
-    #[allow(clippy::ptr_arg)]
-    fn encode(&self, v: &mut Vec<u8>);
-}
-
-impl<T> Encode for T
-where
-    T: Serialize,
-{
-    fn encode(&self, v: &mut Vec<u8>) {
-        v.extend(serde_json::to_vec(self).unwrap());
-    }
-}
-
-trait Decode {
-    fn decode(v: bytes::Bytes) -> Self;
-}
-
-// TODO(justin): figure out how to do this without DeserializeOwned
-impl<T> Decode for T
-where
-    T: 'static + DeserializeOwned,
-{
-    fn decode(v: bytes::Bytes) -> Self {
-        let st = std::str::from_utf8(&v).unwrap().to_owned();
-        serde_json::from_str(&st).unwrap()
-    }
-}
-
 const CONTACTS_ADDR: u32 = 0;
 const DIAGNOSES_ADDR: u32 = 1;
 
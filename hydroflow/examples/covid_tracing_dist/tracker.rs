@@ -1,6 +1,7 @@
-use crate::{Decode, Encode, Opts, CONTACTS_ADDR, DIAGNOSES_ADDR};
+use crate::{Opts, CONTACTS_ADDR, DIAGNOSES_ADDR};
 
 use hydroflow::lang::collections::Iter;
+use hydroflow::scheduled::net::codec::{Decode, Encode, JsonCodec};
 use hydroflow::scheduled::{graph::Hydroflow, handoff::VecHandoff, net::Message};
 use hydroflow::tokio::net::TcpStream;
 use hydroflow::{
@@ -31,14 +32,12 @@ pub(crate) async fn run_tracker(opts: Opts) {
                 let Message { address, batch } = message;
                 match address {
                     CONTACTS_ADDR => {
-                        send1.give(Iter(
-                            <Vec<(String, String, usize)>>::decode(batch).into_iter(),
-                        ));
+                        let batch: Vec<(String, String, usize)> = JsonCodec.decode(batch).unwrap();
+                        send1.give(Iter(batch.into_iter()));
                     }
                     DIAGNOSES_ADDR => {
-                        send2.give(Iter(
-                            <Vec<(String, (usize, usize))>>::decode(batch).into_iter(),
-                        ));
+                        let batch: Vec<(String, (usize, usize))> = JsonCodec.decode(batch).unwrap();
+                        send2.give(Iter(batch.into_iter()));
                     }
                     _ => panic!("invalid port"),
                 }
@@ -102,12 +101,8 @@ pub(crate) async fn run_tracker(opts: Opts) {
         encoder_in,
         network_out,
         |_ctx, recv, send| {
-            let mut buf = Vec::new();
-            recv.take_inner().encode(&mut buf);
-            send.give(Some(Message {
-                address: 0,
-                batch: buf.into(),
-            }));
+            let batch = JsonCodec.encode(&recv.take_inner()).unwrap();
+            send.give(Some(Message { address: 0, batch }));
         },
     );
 
@@ -6,19 +6,20 @@ pub fn main() {
 
     let (send_edges, recv_edges) =
         builder.add_channel_input::<_, _, VecHandoff<(usize, usize)>>("edge input");
-    let (send_loop, recv_loop) = builder.make_edge::<_, VecHandoff<usize>, _>("loop");
 
-    builder.add_subgraph(
-        "main",
-        [0].into_hydroflow()
-            .chain(recv_loop.flatten())
+    let reached = builder.iterate("reach", [0].into_hydroflow(), move |_builder, reached| {
+        reached
             .map(|v| (v, ()))
             .join(recv_edges.flatten())
-            .pull_to_push()
             .map(|(_old_v, (), new_v)| new_v)
-            .inspect(|&v| println!("Reached: {}", v))
-            .map(Some)
-            .push_to(send_loop),
+    });
+
+    builder.add_subgraph(
+        "print",
+        reached
+            .flatten()
+            .pull_to_push()
+            .for_each(|v| println!("Reached: {}", v)),
     );
 
     let mut hydroflow = builder.build();
@@ -1,5 +1,6 @@
 use hydroflow::builder::prelude::*;
 use hydroflow::scheduled::handoff::VecHandoff;
+use hydroflow::tl;
 
 //This example detects size three cliques in a graph. Size three cliques are also known as triangles.
 //The equivalent datalog program would be Triangle(x,y,z) := Edge(x,y), Edge(y,z), Edge(z,x)
@@ -14,13 +15,11 @@ pub fn main() {
 
     builder.add_subgraph(
         "teeing",
-        recv_edges.flatten().pull_to_push().map(Some).tee(
+        recv_edges.flatten().pull_to_push().map(Some).tee_n(tl!(
             builder.start_tee().push_to(send_a),
-            builder.start_tee().tee(
-                builder.start_tee().push_to(send_b),
-                builder.start_tee().push_to(send_c),
-            ),
-        ),
+            builder.start_tee().push_to(send_b),
+            builder.start_tee().push_to(send_c),
+        )),
     );
 
     builder.add_subgraph(
@@ -9,8 +9,15 @@ pub mod pull_flatten;
 pub mod pull_handoff;
 pub mod pull_iter;
 pub mod pull_join;
+pub mod pull_lattice_join;
 pub mod pull_map;
+pub mod pull_map_async;
+pub mod pull_merge;
+pub mod pull_sort;
+pub mod pull_unique;
+pub mod pull_zip;
 
+pub mod push_demux;
 pub mod push_filter;
 pub mod push_filter_map;
 pub mod push_flatten;
@@ -19,11 +26,15 @@ pub mod push_handoff;
 pub mod push_map;
 pub mod push_partition;
 pub mod push_tee;
+pub mod push_tee_n;
+pub mod push_unique;
 
-use crate::compiled::Pusherator;
+use crate::compiled::pull::IteratorList;
+use crate::compiled::{Pusherator, PusheratorList};
 use crate::scheduled::context::Context;
-use crate::scheduled::handoff::handoff_list::PortList;
+use crate::scheduled::handoff::handoff_list::{PortList, PortListSplit};
 use crate::scheduled::port::{RECV, SEND};
+use crate::scheduled::type_list::{Extend, TypeList};
 
 pub trait PullBuildBase {
     type ItemOut;
@@ -40,6 +51,67 @@ pub trait PullBuild: PullBuildBase {
     ) -> Self::Build<'slf, 'hof>;
 }
 
+/// A variadic list of [`PullBuild`]s which all yield the same `T`, for N-ary
+/// pull operators.
+pub trait PullBuildListBase<T>: TypeList {
+    type Build<'slf, 'hof>: IteratorList<T>;
+}
+pub trait PullBuildList<T>: PullBuildListBase<T> {
+    type InputHandoffs: PortList<RECV>;
+
+    /// Builds the list of iterators for a single run of the subgraph.
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        handoffs: <Self::InputHandoffs as PortList<RECV>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof>;
+}
+impl<T, Prev, Rest> PullBuildListBase<T> for (Prev, Rest)
+where
+    Prev: PullBuild<ItemOut = T>,
+    Rest: PullBuildList<T>,
+{
+    type Build<'slf, 'hof> = (Prev::Build<'slf, 'hof>, Rest::Build<'slf, 'hof>);
+}
+impl<T, Prev, Rest> PullBuildList<T> for (Prev, Rest)
+where
+    Prev: PullBuild<ItemOut = T>,
+    Rest: PullBuildList<T>,
+
+    Prev::InputHandoffs: Extend<Rest::InputHandoffs>,
+    <Prev::InputHandoffs as Extend<Rest::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, Prev::InputHandoffs, Suffix = Rest::InputHandoffs>,
+{
+    type InputHandoffs = <Prev::InputHandoffs as Extend<Rest::InputHandoffs>>::Extended;
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        handoffs: <Self::InputHandoffs as PortList<RECV>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+        let (prev, rest) = self;
+        let (handoffs_prev, handoffs_rest) =
+            <Self::InputHandoffs as PortListSplit<_, _>>::split_ctx(handoffs);
+        (
+            prev.build(context, handoffs_prev),
+            rest.build(context, handoffs_rest),
+        )
+    }
+}
+impl<T> PullBuildListBase<T> for () {
+    type Build<'slf, 'hof> = ();
+}
+impl<T> PullBuildList<T> for () {
+    type InputHandoffs = ();
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        _context: &Context<'_>,
+        _handoffs: <Self::InputHandoffs as PortList<RECV>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+    }
+}
+
 pub trait PushBuildBase {
     type ItemIn;
     type Build<'slf, 'hof>: Pusherator<Item = Self::ItemIn>;
@@ -54,3 +126,64 @@ pub trait PushBuild: PushBuildBase {
         handoffs: <Self::OutputHandoffs as PortList<SEND>>::Ctx<'hof>,
     ) -> Self::Build<'slf, 'hof>;
 }
+
+/// A variadic list of [`PushBuild`]s which all accept the same `T`, for N-ary
+/// push operators.
+pub trait PushBuildListBase<T>: TypeList {
+    type Build<'slf, 'hof>: PusheratorList<T>;
+}
+pub trait PushBuildList<T>: PushBuildListBase<T> {
+    type OutputHandoffs: PortList<SEND>;
+
+    /// Builds the list of pusherators for a single run of the subgraph.
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        handoffs: <Self::OutputHandoffs as PortList<SEND>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof>;
+}
+impl<T, Next, Rest> PushBuildListBase<T> for (Next, Rest)
+where
+    Next: PushBuild<ItemIn = T>,
+    Rest: PushBuildList<T>,
+{
+    type Build<'slf, 'hof> = (Next::Build<'slf, 'hof>, Rest::Build<'slf, 'hof>);
+}
+impl<T, Next, Rest> PushBuildList<T> for (Next, Rest)
+where
+    Next: PushBuild<ItemIn = T>,
+    Rest: PushBuildList<T>,
+
+    Next::OutputHandoffs: Extend<Rest::OutputHandoffs>,
+    <Next::OutputHandoffs as Extend<Rest::OutputHandoffs>>::Extended:
+        PortList<SEND> + PortListSplit<SEND, Next::OutputHandoffs, Suffix = Rest::OutputHandoffs>,
+{
+    type OutputHandoffs = <Next::OutputHandoffs as Extend<Rest::OutputHandoffs>>::Extended;
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        handoffs: <Self::OutputHandoffs as PortList<SEND>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+        let (next, rest) = self;
+        let (handoffs_next, handoffs_rest) =
+            <Self::OutputHandoffs as PortListSplit<_, _>>::split_ctx(handoffs);
+        (
+            next.build(context, handoffs_next),
+            rest.build(context, handoffs_rest),
+        )
+    }
+}
+impl<T> PushBuildListBase<T> for () {
+    type Build<'slf, 'hof> = ();
+}
+impl<T> PushBuildList<T> for () {
+    type OutputHandoffs = ();
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        _context: &Context<'_>,
+        _handoffs: <Self::OutputHandoffs as PortList<SEND>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+    }
+}
@@ -0,0 +1,112 @@
+use super::{PullBuild, PullBuildBase};
+
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::compiled::pull::{LatticeJoin, LatticeJoinState};
+use crate::lang::lattice::{Convert, LatticeRepr, Merge};
+use crate::scheduled::context::Context;
+use crate::scheduled::handoff::handoff_list::{PortList, PortListSplit};
+use crate::scheduled::port::RECV;
+use crate::scheduled::type_list::Extend;
+
+pub struct LatticeJoinPullBuild<PrevA, PrevB, Key, LrA, DeltaA, LrB, DeltaB>
+where
+    PrevA: PullBuild<ItemOut = (Key, DeltaA::Repr)>,
+    PrevB: PullBuild<ItemOut = (Key, DeltaB::Repr)>,
+    Key: 'static + Eq + Hash + Clone,
+    LrA: 'static + LatticeRepr<Lattice = DeltaA::Lattice> + Merge<DeltaA>,
+    DeltaA: 'static + LatticeRepr + Convert<LrA>,
+    LrB: 'static + LatticeRepr<Lattice = DeltaB::Lattice> + Merge<DeltaB>,
+    DeltaB: 'static + LatticeRepr + Convert<LrB>,
+
+    PrevA::InputHandoffs: Extend<PrevB::InputHandoffs>,
+    <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, PrevA::InputHandoffs, Suffix = PrevB::InputHandoffs>,
+{
+    prev_a: PrevA,
+    prev_b: PrevB,
+    state: LatticeJoinState<Key, LrA, LrB>,
+    _phantom: PhantomData<fn(DeltaA, DeltaB)>,
+}
+impl<PrevA, PrevB, Key, LrA, DeltaA, LrB, DeltaB>
+    LatticeJoinPullBuild<PrevA, PrevB, Key, LrA, DeltaA, LrB, DeltaB>
+where
+    PrevA: PullBuild<ItemOut = (Key, DeltaA::Repr)>,
+    PrevB: PullBuild<ItemOut = (Key, DeltaB::Repr)>,
+    Key: 'static + Eq + Hash + Clone,
+    LrA: 'static + LatticeRepr<Lattice = DeltaA::Lattice> + Merge<DeltaA>,
+    DeltaA: 'static + LatticeRepr + Convert<LrA>,
+    LrB: 'static + LatticeRepr<Lattice = DeltaB::Lattice> + Merge<DeltaB>,
+    DeltaB: 'static + LatticeRepr + Convert<LrB>,
+
+    PrevA::InputHandoffs: Extend<PrevB::InputHandoffs>,
+    <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, PrevA::InputHandoffs, Suffix = PrevB::InputHandoffs>,
+{
+    pub fn new(prev_a: PrevA, prev_b: PrevB) -> Self {
+        Self {
+            prev_a,
+            prev_b,
+            state: Default::default(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<PrevA, PrevB, Key, LrA, DeltaA, LrB, DeltaB> PullBuildBase
+    for LatticeJoinPullBuild<PrevA, PrevB, Key, LrA, DeltaA, LrB, DeltaB>
+where
+    PrevA: PullBuild<ItemOut = (Key, DeltaA::Repr)>,
+    PrevB: PullBuild<ItemOut = (Key, DeltaB::Repr)>,
+    Key: 'static + Eq + Hash + Clone,
+    LrA: 'static + LatticeRepr<Lattice = DeltaA::Lattice> + Merge<DeltaA>,
+    DeltaA: 'static + LatticeRepr + Convert<LrA>,
+    LrB: 'static + LatticeRepr<Lattice = DeltaB::Lattice> + Merge<DeltaB>,
+    DeltaB: 'static + LatticeRepr + Convert<LrB>,
+
+    PrevA::InputHandoffs: Extend<PrevB::InputHandoffs>,
+    <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, PrevA::InputHandoffs, Suffix = PrevB::InputHandoffs>,
+{
+    type ItemOut = (Key, LrA::Repr, LrB::Repr);
+    type Build<'slf, 'hof> = LatticeJoin<
+        'slf,
+        Key,
+        PrevA::Build<'slf, 'hof>,
+        LrA,
+        DeltaA,
+        PrevB::Build<'slf, 'hof>,
+        LrB,
+        DeltaB,
+    >;
+}
+
+impl<PrevA, PrevB, Key, LrA, DeltaA, LrB, DeltaB> PullBuild
+    for LatticeJoinPullBuild<PrevA, PrevB, Key, LrA, DeltaA, LrB, DeltaB>
+where
+    PrevA: PullBuild<ItemOut = (Key, DeltaA::Repr)>,
+    PrevB: PullBuild<ItemOut = (Key, DeltaB::Repr)>,
+    Key: 'static + Eq + Hash + Clone,
+    LrA: 'static + LatticeRepr<Lattice = DeltaA::Lattice> + Merge<DeltaA>,
+    DeltaA: 'static + LatticeRepr + Convert<LrA>,
+    LrB: 'static + LatticeRepr<Lattice = DeltaB::Lattice> + Merge<DeltaB>,
+    DeltaB: 'static + LatticeRepr + Convert<LrB>,
+
+    PrevA::InputHandoffs: Extend<PrevB::InputHandoffs>,
+    <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, PrevA::InputHandoffs, Suffix = PrevB::InputHandoffs>,
+{
+    type InputHandoffs = <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended;
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        input: <Self::InputHandoffs as PortList<RECV>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+        let (input_a, input_b) = <Self::InputHandoffs as PortListSplit<_, _>>::split_ctx(input);
+        let iter_a = self.prev_a.build(context, input_a);
+        let iter_b = self.prev_b.build(context, input_b);
+        LatticeJoin::new(iter_a, iter_b, &mut self.state)
+    }
+}
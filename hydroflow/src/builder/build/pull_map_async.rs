@@ -0,0 +1,64 @@
+use super::{PullBuild, PullBuildBase};
+
+use std::future::Future;
+
+use crate::compiled::map_async::{MapAsync, MapAsyncState};
+use crate::scheduled::{context::Context, handoff::handoff_list::PortList, port::RECV};
+
+pub struct MapAsyncPullBuild<Prev, Func, Fut>
+where
+    Prev: PullBuild,
+    Fut: Future,
+{
+    prev: Prev,
+    func: Func,
+    state: MapAsyncState<Prev::ItemOut, Fut>,
+}
+impl<Prev, Func, Fut> MapAsyncPullBuild<Prev, Func, Fut>
+where
+    Prev: PullBuild,
+    Func: FnMut(Prev::ItemOut) -> Fut,
+    Fut: Future,
+{
+    pub fn new(prev: Prev, func: Func, limit: usize, ordered: bool) -> Self {
+        Self {
+            prev,
+            func,
+            state: MapAsyncState::new(limit, ordered),
+        }
+    }
+}
+
+impl<Prev, Func, Fut> PullBuildBase for MapAsyncPullBuild<Prev, Func, Fut>
+where
+    Prev: PullBuild,
+    Prev::ItemOut: 'static,
+    Func: 'static + FnMut(Prev::ItemOut) -> Fut,
+    Fut: 'static + Future,
+{
+    type ItemOut = Fut::Output;
+    type Build<'slf, 'hof> = MapAsync<'slf, Prev::Build<'slf, 'hof>, Func, Fut>;
+}
+
+impl<Prev, Func, Fut> PullBuild for MapAsyncPullBuild<Prev, Func, Fut>
+where
+    Prev: PullBuild,
+    Prev::ItemOut: 'static,
+    Func: 'static + FnMut(Prev::ItemOut) -> Fut,
+    Fut: 'static + Future,
+{
+    type InputHandoffs = Prev::InputHandoffs;
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        handoffs: <Self::InputHandoffs as PortList<RECV>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+        MapAsync::new(
+            self.prev.build(context, handoffs),
+            &mut self.func,
+            &mut self.state,
+            context.waker(),
+        )
+    }
+}
@@ -0,0 +1,50 @@
+use super::{PullBuild, PullBuildBase, PullBuildList};
+
+use std::marker::PhantomData;
+
+use crate::compiled::pull::RoundRobin;
+use crate::scheduled::context::Context;
+use crate::scheduled::handoff::handoff_list::PortList;
+use crate::scheduled::port::RECV;
+
+pub struct MergePullBuild<Prevs, T>
+where
+    Prevs: PullBuildList<T>,
+{
+    prevs: Prevs,
+    _phantom: PhantomData<fn() -> T>,
+}
+impl<Prevs, T> MergePullBuild<Prevs, T>
+where
+    Prevs: PullBuildList<T>,
+{
+    pub fn new(prevs: Prevs) -> Self {
+        Self {
+            prevs,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<Prevs, T> PullBuildBase for MergePullBuild<Prevs, T>
+where
+    Prevs: PullBuildList<T>,
+{
+    type ItemOut = T;
+    type Build<'slf, 'hof> = RoundRobin<T, Prevs::Build<'slf, 'hof>>;
+}
+
+impl<Prevs, T> PullBuild for MergePullBuild<Prevs, T>
+where
+    Prevs: PullBuildList<T>,
+{
+    type InputHandoffs = Prevs::InputHandoffs;
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        handoffs: <Self::InputHandoffs as PortList<RECV>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+        RoundRobin::new(self.prevs.build(context, handoffs))
+    }
+}
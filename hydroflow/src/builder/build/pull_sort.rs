@@ -0,0 +1,65 @@
+use super::{PullBuild, PullBuildBase};
+
+use crate::scheduled::{context::Context, handoff::handoff_list::PortList, port::RECV};
+
+pub struct SortPullBuild<Prev, Func>
+where
+    Prev: PullBuild,
+    Prev::ItemOut: 'static,
+{
+    prev: Prev,
+    func: Func,
+    buffer: Vec<Prev::ItemOut>,
+}
+impl<Prev, Func> SortPullBuild<Prev, Func>
+where
+    Prev: PullBuild,
+    Prev::ItemOut: 'static,
+    Func: FnMut(&mut Vec<Prev::ItemOut>),
+{
+    pub fn new(prev: Prev, func: Func) -> Self {
+        Self {
+            prev,
+            func,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+impl<Prev, Func> PullBuildBase for SortPullBuild<Prev, Func>
+where
+    Prev: PullBuild,
+    Prev::ItemOut: 'static,
+    Func: FnMut(&mut Vec<Prev::ItemOut>),
+{
+    type ItemOut = Prev::ItemOut;
+    type Build<'slf, 'hof> = std::vec::Drain<'slf, Prev::ItemOut>;
+}
+
+impl<Prev, Func> PullBuild for SortPullBuild<Prev, Func>
+where
+    Prev: PullBuild,
+    Prev::ItemOut: 'static,
+    Func: FnMut(&mut Vec<Prev::ItemOut>),
+{
+    type InputHandoffs = Prev::InputHandoffs;
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        handoffs: <Self::InputHandoffs as PortList<RECV>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+        self.buffer.extend(self.prev.build(context, handoffs));
+
+        if context.is_end_of_tick() {
+            (self.func)(&mut self.buffer);
+            self.buffer.drain(..)
+        } else {
+            // Hold everything until the rest of the tick's input has arrived.
+            if !self.buffer.is_empty() {
+                context.schedule_end_of_tick();
+            }
+            self.buffer.drain(0..0)
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use super::{PullBuild, PullBuildBase};
+
+use std::hash::Hash;
+
+use crate::compiled::unique::UniqueState;
+use crate::scheduled::{context::Context, handoff::handoff_list::PortList, port::RECV};
+
+pub struct UniquePullBuild<Prev>
+where
+    Prev: PullBuild,
+{
+    prev: Prev,
+    state: UniqueState<Prev::ItemOut>,
+}
+impl<Prev> UniquePullBuild<Prev>
+where
+    Prev: PullBuild,
+    Prev::ItemOut: Eq + Hash + Clone,
+{
+    pub fn new(prev: Prev, per_tick: bool) -> Self {
+        Self {
+            prev,
+            state: UniqueState::new(per_tick),
+        }
+    }
+}
+
+#[allow(type_alias_bounds)]
+type PullBuildImpl<'slf, 'hof, Prev>
+where
+    Prev: PullBuild,
+= std::iter::Filter<Prev::Build<'slf, 'hof>, impl FnMut(&Prev::ItemOut) -> bool>;
+
+impl<Prev> PullBuildBase for UniquePullBuild<Prev>
+where
+    Prev: PullBuild,
+    Prev::ItemOut: Eq + Hash + Clone,
+{
+    type ItemOut = Prev::ItemOut;
+    type Build<'slf, 'hof> = PullBuildImpl<'slf, 'hof, Prev>;
+}
+
+impl<Prev> PullBuild for UniquePullBuild<Prev>
+where
+    Prev: PullBuild,
+    Prev::ItemOut: Eq + Hash + Clone,
+{
+    type InputHandoffs = Prev::InputHandoffs;
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        handoffs: <Self::InputHandoffs as PortList<RECV>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+        self.state.start_run(context.current_tick());
+        let state = &mut self.state;
+        self.prev
+            .build(context, handoffs)
+            .filter(move |x| state.insert(x))
+    }
+}
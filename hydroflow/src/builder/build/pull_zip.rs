@@ -0,0 +1,88 @@
+use super::{PullBuild, PullBuildBase};
+
+use crate::compiled::pull::{Zip, ZipState};
+use crate::scheduled::context::Context;
+use crate::scheduled::handoff::handoff_list::{PortList, PortListSplit};
+use crate::scheduled::port::RECV;
+use crate::scheduled::type_list::Extend;
+
+pub struct ZipPullBuild<PrevA, PrevB>
+where
+    PrevA: PullBuild,
+    PrevB: PullBuild,
+    PrevA::ItemOut: 'static,
+    PrevB::ItemOut: 'static,
+
+    PrevA::InputHandoffs: Extend<PrevB::InputHandoffs>,
+    <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, PrevA::InputHandoffs, Suffix = PrevB::InputHandoffs>,
+{
+    prev_a: PrevA,
+    prev_b: PrevB,
+    state: ZipState<PrevA::ItemOut, PrevB::ItemOut>,
+}
+impl<PrevA, PrevB> ZipPullBuild<PrevA, PrevB>
+where
+    PrevA: PullBuild,
+    PrevB: PullBuild,
+    PrevA::ItemOut: 'static,
+    PrevB::ItemOut: 'static,
+
+    PrevA::InputHandoffs: Extend<PrevB::InputHandoffs>,
+    <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, PrevA::InputHandoffs, Suffix = PrevB::InputHandoffs>,
+{
+    pub fn new(prev_a: PrevA, prev_b: PrevB) -> Self {
+        Self {
+            prev_a,
+            prev_b,
+            state: Default::default(),
+        }
+    }
+}
+
+impl<PrevA, PrevB> PullBuildBase for ZipPullBuild<PrevA, PrevB>
+where
+    PrevA: PullBuild,
+    PrevB: PullBuild,
+    PrevA::ItemOut: 'static,
+    PrevB::ItemOut: 'static,
+
+    PrevA::InputHandoffs: Extend<PrevB::InputHandoffs>,
+    <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, PrevA::InputHandoffs, Suffix = PrevB::InputHandoffs>,
+{
+    type ItemOut = (PrevA::ItemOut, PrevB::ItemOut);
+    type Build<'slf, 'hof> = Zip<
+        'slf,
+        PrevA::Build<'slf, 'hof>,
+        PrevA::ItemOut,
+        PrevB::Build<'slf, 'hof>,
+        PrevB::ItemOut,
+    >;
+}
+
+impl<PrevA, PrevB> PullBuild for ZipPullBuild<PrevA, PrevB>
+where
+    PrevA: PullBuild,
+    PrevB: PullBuild,
+    PrevA::ItemOut: 'static,
+    PrevB::ItemOut: 'static,
+
+    PrevA::InputHandoffs: Extend<PrevB::InputHandoffs>,
+    <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, PrevA::InputHandoffs, Suffix = PrevB::InputHandoffs>,
+{
+    type InputHandoffs = <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended;
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        input: <Self::InputHandoffs as PortList<RECV>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+        let (input_a, input_b) = <Self::InputHandoffs as PortListSplit<_, _>>::split_ctx(input);
+        let iter_a = self.prev_a.build(context, input_a);
+        let iter_b = self.prev_b.build(context, input_b);
+        Zip::new(iter_a, iter_b, &mut self.state)
+    }
+}
@@ -0,0 +1,63 @@
+use super::{PushBuild, PushBuildBase, PushBuildList};
+
+use std::marker::PhantomData;
+
+use crate::compiled::demux::Demux;
+use crate::scheduled::context::Context;
+use crate::scheduled::handoff::handoff_list::PortList;
+use crate::scheduled::port::SEND;
+
+pub struct DemuxPushBuild<Nexts, Func, T>
+where
+    Nexts: PushBuildList<T>,
+    Func: Fn(&T) -> usize,
+{
+    func: Func,
+    nexts: Nexts,
+    _phantom: PhantomData<fn(T)>,
+}
+impl<Nexts, Func, T> DemuxPushBuild<Nexts, Func, T>
+where
+    Nexts: PushBuildList<T>,
+    Func: Fn(&T) -> usize,
+{
+    pub fn new(func: Func, nexts: Nexts) -> Self {
+        Self {
+            func,
+            nexts,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[allow(type_alias_bounds)]
+type PushBuildImpl<'slf, 'hof, Nexts, Func, T>
+where
+    Nexts: PushBuildList<T>,
+= Demux<T, impl Fn(&T) -> usize, Nexts::Build<'slf, 'hof>>;
+
+impl<Nexts, Func, T> PushBuildBase for DemuxPushBuild<Nexts, Func, T>
+where
+    Nexts: PushBuildList<T>,
+    Func: Fn(&T) -> usize,
+{
+    type ItemIn = T;
+    type Build<'slf, 'hof> = PushBuildImpl<'slf, 'hof, Nexts, Func, T>;
+}
+
+impl<Nexts, Func, T> PushBuild for DemuxPushBuild<Nexts, Func, T>
+where
+    Nexts: PushBuildList<T>,
+    Func: Fn(&T) -> usize,
+{
+    type OutputHandoffs = Nexts::OutputHandoffs;
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        handoffs: <Self::OutputHandoffs as PortList<SEND>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+        let nexts = self.nexts.build(context, handoffs);
+        Demux::new(|x| (self.func)(x), nexts)
+    }
+}
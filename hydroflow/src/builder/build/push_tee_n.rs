@@ -0,0 +1,54 @@
+use super::{PushBuild, PushBuildBase, PushBuildList};
+
+use std::marker::PhantomData;
+
+use crate::compiled::tee::TeeN;
+use crate::scheduled::context::Context;
+use crate::scheduled::handoff::handoff_list::PortList;
+use crate::scheduled::port::SEND;
+
+pub struct TeeNPushBuild<Nexts, T>
+where
+    Nexts: PushBuildList<T>,
+    T: Clone,
+{
+    nexts: Nexts,
+    _phantom: PhantomData<fn(T)>,
+}
+impl<Nexts, T> TeeNPushBuild<Nexts, T>
+where
+    Nexts: PushBuildList<T>,
+    T: Clone,
+{
+    pub fn new(nexts: Nexts) -> Self {
+        Self {
+            nexts,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<Nexts, T> PushBuildBase for TeeNPushBuild<Nexts, T>
+where
+    Nexts: PushBuildList<T>,
+    T: Clone,
+{
+    type ItemIn = T;
+    type Build<'slf, 'hof> = TeeN<T, Nexts::Build<'slf, 'hof>>;
+}
+
+impl<Nexts, T> PushBuild for TeeNPushBuild<Nexts, T>
+where
+    Nexts: PushBuildList<T>,
+    T: Clone,
+{
+    type OutputHandoffs = Nexts::OutputHandoffs;
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        handoffs: <Self::OutputHandoffs as PortList<SEND>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+        TeeN::new(self.nexts.build(context, handoffs))
+    }
+}
@@ -0,0 +1,62 @@
+use super::{PushBuild, PushBuildBase};
+
+use std::hash::Hash;
+
+use crate::compiled::filter::Filter;
+use crate::compiled::unique::UniqueState;
+use crate::scheduled::context::Context;
+use crate::scheduled::handoff::handoff_list::PortList;
+use crate::scheduled::port::SEND;
+
+pub struct UniquePushBuild<Next>
+where
+    Next: PushBuild,
+{
+    next: Next,
+    state: UniqueState<Next::ItemIn>,
+}
+impl<Next> UniquePushBuild<Next>
+where
+    Next: PushBuild,
+    Next::ItemIn: Eq + Hash + Clone,
+{
+    pub fn new(next: Next, per_tick: bool) -> Self {
+        Self {
+            next,
+            state: UniqueState::new(per_tick),
+        }
+    }
+}
+
+#[allow(type_alias_bounds)]
+type PushBuildImpl<'slf, 'hof, Next>
+where
+    Next: PushBuild,
+= Filter<Next::ItemIn, impl FnMut(&Next::ItemIn) -> bool, Next::Build<'slf, 'hof>>;
+
+impl<Next> PushBuildBase for UniquePushBuild<Next>
+where
+    Next: PushBuild,
+    Next::ItemIn: Eq + Hash + Clone,
+{
+    type ItemIn = Next::ItemIn;
+    type Build<'slf, 'hof> = PushBuildImpl<'slf, 'hof, Next>;
+}
+
+impl<Next> PushBuild for UniquePushBuild<Next>
+where
+    Next: PushBuild,
+    Next::ItemIn: Eq + Hash + Clone,
+{
+    type OutputHandoffs = Next::OutputHandoffs;
+
+    fn build<'slf, 'hof>(
+        &'slf mut self,
+        context: &Context<'_>,
+        handoffs: <Self::OutputHandoffs as PortList<SEND>>::Ctx<'hof>,
+    ) -> Self::Build<'slf, 'hof> {
+        self.state.start_run(context.current_tick());
+        let state = &mut self.state;
+        Filter::new(move |x| state.insert(x), self.next.build(context, handoffs))
+    }
+}
@@ -0,0 +1,50 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A shared handle to the items collected by
+/// [`PushSurface::collect_into`](super::surface::PushSurface::collect_into).
+///
+/// Items accumulate across ticks until they are removed with [`Self::take`].
+pub struct CollectHandle<T> {
+    items: Rc<RefCell<Vec<T>>>,
+}
+
+impl<T> Default for CollectHandle<T> {
+    fn default() -> Self {
+        Self {
+            items: Default::default(),
+        }
+    }
+}
+
+impl<T> Clone for CollectHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+        }
+    }
+}
+
+impl<T> CollectHandle<T> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Appends an item, used by the collecting subgraph.
+    pub fn push(&self, item: T) {
+        (*self.items).borrow_mut().push(item);
+    }
+
+    /// Removes and returns all items collected so far.
+    pub fn take(&self) -> Vec<T> {
+        self.items.take()
+    }
+
+    /// Returns a copy of all items collected so far, leaving them in place.
+    pub fn snapshot(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.items.borrow().clone()
+    }
+}
@@ -1,25 +1,35 @@
-use futures::Stream;
+use futures::{Sink, Stream, StreamExt};
 
 use super::build::{PullBuild, PushBuild};
 use super::surface::pivot::PivotSurface;
 use super::surface::pull_iter::IterPullSurface;
 
 use std::borrow::Cow;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::mpsc::SyncSender;
 
 use crate::compiled::pivot::Pivot;
+use crate::lang::collections::Iter;
+use crate::lang::event_time::{BoundedDelay, Event, Timestamp};
 use crate::scheduled::graph::Hydroflow;
 use crate::scheduled::graph_ext::GraphExt;
 use crate::scheduled::handoff::{CanReceive, Handoff, VecHandoff};
 use crate::scheduled::input::Input;
+use crate::scheduled::io::csv;
+#[cfg(feature = "http")]
+use crate::scheduled::net::http::{Request, ResponderHandle, Response};
 use crate::scheduled::net::Message;
 use crate::scheduled::port::{RecvPort, SendPort};
 use crate::scheduled::SubgraphId;
 
+use super::surface::flatten::FlattenSurface;
 use super::surface::pull_handoff::HandoffPullSurface;
 use super::surface::push_handoff::HandoffPushSurfaceReversed;
 use super::surface::push_start::StartPushSurface;
-use super::surface::{PullSurface, PushSurfaceReversed};
+use super::surface::{BaseSurface, PullSurface, PushSurface, PushSurfaceReversed};
 
 /// The user-facing entry point for the Surface API.
 #[derive(Default)]
@@ -83,6 +93,94 @@ impl HydroflowBuilder {
         )
     }
 
+    /// Computes the fixpoint of `body` starting from `input`. Every distinct
+    /// item from `input` and every distinct item derived by `body` is fed
+    /// back into `body` until no new items appear, and each one is emitted
+    /// once on the returned surface.
+    ///
+    /// `body` is given this builder, for creating any other inputs it needs,
+    /// and the stream of newly discovered items. Items are deduplicated
+    /// across ticks, so later ticks only extend the fixpoint.
+    ///
+    /// For example, graph reachability:
+    /// ```ignore
+    /// let reached = builder.iterate("reach", [0].into_hydroflow(), move |_builder, reached| {
+    ///     reached
+    ///         .map(|v| (v, ()))
+    ///         .join(edges.flatten())
+    ///         .map(|(_old_v, (), new_v)| new_v)
+    /// });
+    /// ```
+    pub fn iterate<Name, T, Pull, Func, Body>(
+        &mut self,
+        name: Name,
+        input: Pull,
+        body: Func,
+    ) -> HandoffPullSurface<VecHandoff<T>>
+    where
+        Name: Into<Cow<'static, str>>,
+        T: 'static + Eq + Hash + Clone,
+        Pull: 'static + PullSurface<ItemOut = T>,
+        Func: FnOnce(&mut Self, FlattenSurface<HandoffPullSurface<VecHandoff<T>>>) -> Body,
+        Body: 'static + PullSurface<ItemOut = T>,
+    {
+        let name = name.into();
+        let (loop_send, loop_recv) =
+            self.make_edge::<_, VecHandoff<T>, _>(format!("{} loop", name));
+        let (delta_send, delta_recv) =
+            self.make_edge::<_, VecHandoff<T>, _>(format!("{} delta", name));
+        let (out_send, out_recv) =
+            self.make_edge::<_, VecHandoff<T>, _>(format!("{} output", name));
+
+        self.add_subgraph(
+            format!("{} dedup", name),
+            loop_recv
+                .flatten()
+                .chain(input)
+                .pull_to_push()
+                .unique()
+                .map(Some)
+                .tee(delta_send, out_send),
+        );
+
+        let body = (body)(self, delta_recv.flatten());
+        self.add_subgraph(
+            format!("{} body", name),
+            body.pull_to_push().map(Some).push_to(loop_send),
+        );
+
+        out_recv
+    }
+
+    /// Creates a new external channel input which takes individual items,
+    /// returning a pull surface of those items.
+    #[allow(clippy::type_complexity)]
+    pub fn add_vec_input<Name, T>(
+        &mut self,
+        name: Name,
+    ) -> (
+        Input<T, SyncSender<T>>,
+        FlattenSurface<HandoffPullSurface<VecHandoff<T>>>,
+    )
+    where
+        Name: Into<Cow<'static, str>>,
+        T: 'static,
+    {
+        let name = name.into();
+        let (send_port, recv_port) = self.hydroflow.make_edge(format!("{} handoff", name));
+
+        let (sender, receiver) = std::sync::mpsc::sync_channel(8000);
+        let sg_id = self.hydroflow.add_subgraph_source::<_, _, VecHandoff<T>>(
+            name,
+            send_port,
+            move |_ctx, send| {
+                send.give(Iter(receiver.try_iter()));
+            },
+        );
+        let input = Input::new(self.hydroflow.reactor(), sg_id, sender);
+        (input, HandoffPullSurface::new(recv_port).flatten())
+    }
+
     /// Creates a new external channel input.
     pub fn add_channel_input<Name, T, W>(
         &mut self,
@@ -118,6 +216,116 @@ impl HydroflowBuilder {
         pull
     }
 
+    /// Like [Self::add_input_from_stream], but attaches event times and
+    /// watermarks to the items, as [`Event`]s. `time_of` gives the time of
+    /// each item, and items may arrive up to `max_delay` behind the latest
+    /// one seen (see [`BoundedDelay`]). When the stream ends, a final
+    /// watermark of [`Timestamp::MAX`] closes any open windows.
+    pub fn add_input_from_stream_with_watermarks<Name, T, S, F>(
+        &mut self,
+        name: Name,
+        stream: S,
+        time_of: F,
+        max_delay: Timestamp,
+    ) -> HandoffPullSurface<VecHandoff<Event<T>>>
+    where
+        Name: Into<Cow<'static, str>>,
+        T: 'static,
+        S: 'static + Stream<Item = T> + Unpin,
+        F: 'static + FnMut(&T) -> Timestamp,
+    {
+        let mut delay = BoundedDelay::new(time_of, max_delay);
+        let events = stream
+            .flat_map(move |item| futures::stream::iter(delay.attach(item)))
+            .chain(futures::stream::once(futures::future::ready(
+                Event::Watermark(Timestamp::MAX),
+            )))
+            .map(Some);
+        self.add_input_from_stream(name, events)
+    }
+
+    /// Creates an output which sends items into `sink`, respecting its
+    /// backpressure.
+    pub fn add_output_to_sink<Name, T, S>(
+        &mut self,
+        name: Name,
+        sink: S,
+    ) -> HandoffPushSurfaceReversed<VecHandoff<T>, Option<T>>
+    where
+        Name: Into<Cow<'static, str>>,
+        T: 'static,
+        S: 'static + Sink<T> + Unpin,
+        S::Error: Debug,
+    {
+        let name = name.into();
+        let (send_port, recv_port) = self.hydroflow.make_edge(format!("{} handoff", name));
+        self.hydroflow.add_output_to_sink(name, recv_port, sink);
+        HandoffPushSurfaceReversed::new(send_port)
+    }
+
+    /// Produces each line read from stdin.
+    pub fn add_stdin_lines(&mut self) -> HandoffPullSurface<VecHandoff<String>> {
+        let output_port = self.hydroflow.add_stdin_lines();
+        HandoffPullSurface::new(output_port)
+    }
+
+    /// Produces each line of the file at `path`.
+    pub fn add_file_lines(
+        &mut self,
+        path: &Path,
+    ) -> std::io::Result<HandoffPullSurface<VecHandoff<String>>> {
+        let output_port = self.hydroflow.add_file_lines(path)?;
+        Ok(HandoffPullSurface::new(output_port))
+    }
+
+    /// Produces each record of the CSV file at `path`, which must have a
+    /// header row.
+    pub fn add_csv_source<T>(
+        &mut self,
+        path: &Path,
+    ) -> std::io::Result<HandoffPullSurface<VecHandoff<T>>>
+    where
+        T: 'static + serde::de::DeserializeOwned,
+    {
+        let output_port = self.hydroflow.add_csv_source(path)?;
+        Ok(HandoffPullSurface::new(output_port))
+    }
+
+    /// Like [Self::add_csv_source], but reads the file as configured by
+    /// `reader`.
+    pub fn add_csv_source_with_reader<T>(
+        &mut self,
+        reader: &csv::ReaderBuilder,
+        path: &Path,
+    ) -> std::io::Result<HandoffPullSurface<VecHandoff<T>>>
+    where
+        T: 'static + serde::de::DeserializeOwned,
+    {
+        let output_port = self.hydroflow.add_csv_source_with_reader(reader, path)?;
+        Ok(HandoffPullSurface::new(output_port))
+    }
+
+    /// Writes each item to a new file at `path`, one per line.
+    pub fn write_lines<T>(
+        &mut self,
+        path: &Path,
+    ) -> std::io::Result<HandoffPushSurfaceReversed<VecHandoff<T>, Option<T>>>
+    where
+        T: 'static + Display,
+    {
+        let input_port = self.hydroflow.add_write_lines(path)?;
+        Ok(HandoffPushSurfaceReversed::new(input_port))
+    }
+
+    /// Writes each item to stdout, one per line.
+    pub fn add_stdout_lines<T>(&mut self) -> HandoffPushSurfaceReversed<VecHandoff<T>, Option<T>>
+    where
+        T: 'static + Display,
+    {
+        let input_port = self.hydroflow.add_stdout_lines();
+        HandoffPushSurfaceReversed::new(input_port)
+    }
+
     pub fn add_write_tcp_stream(
         &mut self,
         stream: tokio::net::TcpStream,
@@ -152,6 +360,51 @@ impl HydroflowBuilder {
         (push, pull)
     }
 
+    #[allow(clippy::type_complexity)]
+    pub fn add_udp_socket(
+        &mut self,
+        socket: tokio::net::UdpSocket,
+    ) -> (
+        HandoffPushSurfaceReversed<
+            VecHandoff<(SocketAddr, Message)>,
+            Option<(SocketAddr, Message)>,
+        >,
+        HandoffPullSurface<VecHandoff<(SocketAddr, Message)>>,
+    ) {
+        let (input_port, output_port) = self.hydroflow.add_udp_socket(socket);
+
+        let push = HandoffPushSurfaceReversed::new(input_port);
+        let pull = HandoffPullSurface::new(output_port);
+
+        (push, pull)
+    }
+
+    /// Serves HTTP on `addr`, returning the address actually bound and the
+    /// requests received. Answer them with [Self::add_http_responder].
+    #[cfg(feature = "http")]
+    pub fn add_http_server(
+        &mut self,
+        addr: SocketAddr,
+    ) -> (
+        SocketAddr,
+        HandoffPullSurface<VecHandoff<(Request, ResponderHandle)>>,
+    ) {
+        let (addr, output_port) = self.hydroflow.add_http_server(addr);
+        (addr, HandoffPullSurface::new(output_port))
+    }
+
+    #[cfg(feature = "http")]
+    #[allow(clippy::type_complexity)]
+    pub fn add_http_responder(
+        &mut self,
+    ) -> HandoffPushSurfaceReversed<
+        VecHandoff<(ResponderHandle, Response)>,
+        Option<(ResponderHandle, Response)>,
+    > {
+        let input_port = self.hydroflow.add_http_responder();
+        HandoffPushSurfaceReversed::new(input_port)
+    }
+
     pub fn build(self) -> Hydroflow {
         self.hydroflow
     }
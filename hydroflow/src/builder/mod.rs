@@ -79,6 +79,9 @@
 pub mod build;
 pub mod surface;
 
+mod collect_handle;
+pub use collect_handle::CollectHandle;
+
 mod hydroflow_builder;
 pub use hydroflow_builder::HydroflowBuilder;
 
@@ -189,6 +192,665 @@ fn test_partition() {
     );
 }
 
+#[test]
+fn test_merge() {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::scheduled::handoff::VecHandoff;
+    use crate::tl;
+    use prelude::*;
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (a_send, a) = builder.add_channel_input::<_, Option<&'static str>, VecHandoff<_>>("a");
+    let (b_send, b) = builder.add_channel_input::<_, Option<&'static str>, VecHandoff<_>>("b");
+    let (c_send, c) = builder.add_channel_input::<_, Option<&'static str>, VecHandoff<_>>("c");
+
+    let out = Rc::new(RefCell::new(Vec::new()));
+    let out_inner = out.clone();
+
+    builder.add_subgraph(
+        "main",
+        a.flatten()
+            .merge(tl!(b.flatten(), c.flatten()))
+            .pull_to_push()
+            .for_each(move |x| (*out_inner).borrow_mut().push(x)),
+    );
+
+    for x in ["a1", "a2", "a3", "a4"] {
+        a_send.give(Some(x));
+    }
+    b_send.give(Some("b1"));
+    for x in ["c1", "c2"] {
+        c_send.give(Some(x));
+    }
+
+    builder.build().tick();
+
+    assert_eq!(&["a1", "b1", "c1", "a2", "c2", "a3", "a4"], &**out.borrow(),);
+}
+
+#[test]
+fn test_zip() {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::scheduled::handoff::VecHandoff;
+    use prelude::*;
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (reqs_send, reqs) = builder.add_channel_input::<_, Option<u64>, VecHandoff<_>>("reqs");
+    let (resps_send, resps) =
+        builder.add_channel_input::<_, Option<&'static str>, VecHandoff<_>>("resps");
+
+    let out = Rc::new(RefCell::new(Vec::new()));
+    let out_inner = out.clone();
+
+    builder.add_subgraph(
+        "main",
+        reqs.flatten()
+            .zip(resps.flatten())
+            .pull_to_push()
+            .for_each(move |x| (*out_inner).borrow_mut().push(x)),
+    );
+
+    let mut hydroflow = builder.build();
+
+    for x in [1, 2, 3] {
+        reqs_send.give(Some(x));
+    }
+    reqs_send.flush();
+    resps_send.give(Some("one"));
+    resps_send.flush();
+    hydroflow.tick();
+
+    assert_eq!(&[(1, "one")], &**out.borrow());
+
+    for x in ["two", "three", "four"] {
+        resps_send.give(Some(x));
+    }
+    resps_send.flush();
+    hydroflow.tick();
+
+    assert_eq!(&[(1, "one"), (2, "two"), (3, "three")], &**out.borrow());
+
+    reqs_send.give(Some(4));
+    reqs_send.flush();
+    hydroflow.tick();
+
+    assert_eq!(
+        &[(1, "one"), (2, "two"), (3, "three"), (4, "four")],
+        &**out.borrow()
+    );
+}
+
+#[test]
+fn test_tee_n_demux() {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::scheduled::handoff::VecHandoff;
+    use crate::tl;
+    use prelude::*;
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (data_send, data) = builder.add_channel_input::<_, Option<u64>, VecHandoff<_>>("data");
+
+    let out = Rc::new(RefCell::new(Vec::new()));
+    let (all_out, zero_out, one_out, two_out) =
+        (out.clone(), out.clone(), out.clone(), out.clone());
+
+    builder.add_subgraph(
+        "main",
+        data.flatten().pull_to_push().tee_n(tl!(
+            builder
+                .start_tee()
+                .for_each(move |x| (*all_out).borrow_mut().push(("all", x))),
+            // Items with index 3 are past the end of the list, and dropped.
+            builder.start_tee().demux(
+                |x| (*x % 4) as usize,
+                tl!(
+                    builder
+                        .start_tee()
+                        .for_each(move |x| (*zero_out).borrow_mut().push(("zero", x))),
+                    builder
+                        .start_tee()
+                        .for_each(move |x| (*one_out).borrow_mut().push(("one", x))),
+                    builder
+                        .start_tee()
+                        .for_each(move |x| (*two_out).borrow_mut().push(("two", x))),
+                ),
+            ),
+        )),
+    );
+
+    for x in 1..=4 {
+        data_send.give(Some(x));
+    }
+
+    builder.build().tick();
+
+    assert_eq!(
+        &[
+            ("all", 1),
+            ("one", 1),
+            ("all", 2),
+            ("two", 2),
+            ("all", 3),
+            ("all", 4),
+            ("zero", 4),
+        ],
+        &**out.borrow(),
+    );
+}
+
+#[test]
+fn test_collect_into() {
+    use prelude::*;
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (data_send, data) = builder.add_vec_input("data");
+    let (sg, out) = data.map(|x: u64| 2 * x).pull_to_push().collect_into();
+    builder.add_subgraph("main", sg);
+
+    let mut hydroflow = builder.build();
+
+    data_send.give(1);
+    data_send.give(2);
+    data_send.flush();
+    hydroflow.tick();
+
+    assert_eq!(&[2, 4], &*out.snapshot());
+
+    data_send.give(3);
+    data_send.flush();
+    hydroflow.tick();
+
+    assert_eq!(&[2, 4, 6], &*out.take());
+    assert!(out.take().is_empty());
+}
+
+#[test]
+fn test_lattice_ops() {
+    use crate::lang::lattice::ord::MaxRepr;
+    use prelude::*;
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (max_send, max_in) = builder.add_vec_input("max");
+    let (sg, max_out) = max_in
+        .merge_into::<MaxRepr<u64>, MaxRepr<u64>>()
+        .pull_to_push()
+        .collect_into();
+    builder.add_subgraph("max", sg);
+
+    let (thresh_send, thresh_in) = builder.add_vec_input("thresh");
+    let (sg, thresh_out) = thresh_in
+        .merge_into::<MaxRepr<u64>, MaxRepr<u64>>()
+        .threshold(|&x| x >= 10)
+        .pull_to_push()
+        .collect_into();
+    builder.add_subgraph("thresh", sg);
+
+    let (lhs_send, lhs) = builder.add_vec_input("lhs");
+    let (rhs_send, rhs) = builder.add_vec_input("rhs");
+    let (sg, join_out) = lhs
+        .lattice_join::<MaxRepr<u64>, MaxRepr<u64>, MaxRepr<u64>, MaxRepr<u64>, _, _>(rhs)
+        .pull_to_push()
+        .collect_into();
+    builder.add_subgraph("join", sg);
+
+    let mut hydroflow = builder.build();
+
+    for x in [3, 1, 5, 5, 4] {
+        max_send.give(x);
+        thresh_send.give(2 * x);
+    }
+    lhs_send.give(("a", 1));
+    lhs_send.give(("b", 2));
+    rhs_send.give(("a", 7));
+    max_send.flush();
+    thresh_send.flush();
+    lhs_send.flush();
+    rhs_send.flush();
+    hydroflow.tick();
+
+    assert_eq!(&[3, 5], &*max_out.take());
+    assert_eq!(&[10], &*thresh_out.take());
+    assert_eq!(&[("a", 1, 7)], &*join_out.take());
+
+    for x in [2, 8] {
+        max_send.give(x);
+        thresh_send.give(2 * x);
+    }
+    lhs_send.give(("a", 0));
+    rhs_send.give(("b", 1));
+    rhs_send.give(("a", 9));
+    max_send.flush();
+    thresh_send.flush();
+    lhs_send.flush();
+    rhs_send.flush();
+    hydroflow.tick();
+
+    assert_eq!(&[8], &*max_out.take());
+    assert!(thresh_out.take().is_empty());
+    assert_eq!(&[("b", 2, 1), ("a", 1, 9)], &*join_out.take());
+}
+
+#[test]
+fn test_exchange_dynamic() {
+    use crate::lang::partitioner::{rendezvous_hash, stable_hash};
+    use crate::scheduled::handoff::VecHandoff;
+    use prelude::*;
+    use surface::exchange::{Exchange, MembershipChange};
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (membership_send, membership) = builder.add_vec_input("membership");
+    let (data_send, data) = builder.add_vec_input("data");
+    let (remote_send, remote) = builder.add_vec_input::<_, (u64, char)>("remote");
+    let (outbound_send, outbound_recv) =
+        builder.make_edge::<_, VecHandoff<(String, (u64, char))>, _>("outbound");
+
+    let local = data.exchange_dynamic(
+        &mut builder,
+        "exchange",
+        membership,
+        remote,
+        0,
+        outbound_send,
+    );
+    let (sg, local_out) = local.pull_to_push().collect_into();
+    builder.add_subgraph("local", sg);
+    let (sg, outbound_out) = outbound_recv.flatten().pull_to_push().collect_into();
+    builder.add_subgraph("outbound", sg);
+
+    let mut hydroflow = builder.build();
+
+    // Records are held until there is someone to send them to.
+    data_send.give((1, 'a'));
+    data_send.flush();
+    hydroflow.tick();
+    assert!(local_out.take().is_empty());
+
+    membership_send.give(MembershipChange::Join {
+        id: 0,
+        address: "zero".to_owned(),
+    });
+    membership_send.flush();
+    hydroflow.tick();
+    assert_eq!(&[(1, 'a')], &*local_out.take());
+
+    // Records for a joining member are buffered until it is ready.
+    membership_send.give(MembershipChange::Join {
+        id: 1,
+        address: "one".to_owned(),
+    });
+    membership_send.flush();
+    let keys: Vec<u64> = (0..20).collect();
+    for &k in &keys {
+        data_send.give((k, 'b'));
+    }
+    data_send.flush();
+    remote_send.give((100, 'r'));
+    remote_send.flush();
+    hydroflow.tick();
+
+    let owner = |k: &u64| rendezvous_hash(stable_hash(k), [0, 1]).unwrap();
+    let mut expected_local: Vec<_> = keys
+        .iter()
+        .filter(|&k| owner(k) == 0)
+        .map(|&k| (k, 'b'))
+        .collect();
+    expected_local.push((100, 'r'));
+    let expected_remote: Vec<_> = keys
+        .iter()
+        .filter(|&k| owner(k) == 1)
+        .map(|&k| ("one".to_owned(), (k, 'b')))
+        .collect();
+    assert!(!expected_remote.is_empty());
+    assert_eq!(expected_local, local_out.take());
+    assert!(outbound_out.take().is_empty());
+
+    membership_send.give(MembershipChange::Ready { id: 1 });
+    membership_send.flush();
+    hydroflow.tick();
+    assert_eq!(expected_remote, outbound_out.take());
+
+    // Once member 1 leaves, everything is local again.
+    membership_send.give(MembershipChange::Leave { id: 1 });
+    membership_send.flush();
+    for &k in &keys {
+        data_send.give((k, 'c'));
+    }
+    data_send.flush();
+    hydroflow.tick();
+    assert_eq!(
+        keys.iter().map(|&k| (k, 'c')).collect::<Vec<_>>(),
+        local_out.take()
+    );
+    assert!(outbound_out.take().is_empty());
+}
+
+#[test]
+fn test_unique() {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::scheduled::handoff::VecHandoff;
+    use prelude::*;
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (data_send, data) = builder.add_channel_input::<_, Option<u64>, VecHandoff<_>>("data");
+
+    let out_all = Rc::new(RefCell::new(Vec::new()));
+    let out_tick = Rc::new(RefCell::new(Vec::new()));
+    let out_all_inner = out_all.clone();
+    let out_tick_inner = out_tick.clone();
+
+    builder.add_subgraph(
+        "main",
+        data.flatten().pull_to_push().tee(
+            builder
+                .start_tee()
+                .unique()
+                .for_each(move |x| (*out_all_inner).borrow_mut().push(x)),
+            builder
+                .start_tee()
+                .unique_per_tick()
+                .for_each(move |x| (*out_tick_inner).borrow_mut().push(x)),
+        ),
+    );
+
+    let mut hydroflow = builder.build();
+
+    for x in [1, 2, 1, 3, 2] {
+        data_send.give(Some(x));
+    }
+    data_send.flush();
+    hydroflow.tick();
+
+    assert_eq!(&[1, 2, 3], &**out_all.borrow());
+    assert_eq!(&[1, 2, 3], &**out_tick.borrow());
+
+    for x in [3, 4, 3] {
+        data_send.give(Some(x));
+    }
+    data_send.flush();
+    hydroflow.tick();
+
+    assert_eq!(&[1, 2, 3, 4], &**out_all.borrow());
+    assert_eq!(&[1, 2, 3, 3, 4], &**out_tick.borrow());
+}
+
+#[test]
+fn test_map_async() {
+    use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+    use futures::channel::oneshot;
+
+    use crate::scheduled::handoff::VecHandoff;
+    use prelude::*;
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (data_send, data) = builder.add_channel_input::<_, Option<u64>, VecHandoff<_>>("data");
+
+    let senders: Rc<RefCell<HashMap<u64, oneshot::Sender<u64>>>> = Default::default();
+    let senders_inner = senders.clone();
+
+    let out = Rc::new(RefCell::new(Vec::new()));
+    let out_inner = out.clone();
+
+    builder.add_subgraph(
+        "main",
+        data.flatten()
+            .map_async(2, move |x| {
+                let (send, recv) = oneshot::channel();
+                (*senders_inner).borrow_mut().insert(x, send);
+                recv
+            })
+            .map(Result::unwrap)
+            .pull_to_push()
+            .for_each(move |x| (*out_inner).borrow_mut().push(x)),
+    );
+
+    let mut hydroflow = builder.build();
+
+    for x in [1, 2, 3] {
+        data_send.give(Some(x));
+    }
+    data_send.flush();
+    hydroflow.tick();
+
+    // Only two futures are started due to the limit.
+    assert_eq!(2, senders.borrow().len());
+    assert!(out.borrow().is_empty());
+
+    // Output order follows the input order.
+    let complete = |x: u64| {
+        let send = (*senders).borrow_mut().remove(&x).unwrap();
+        send.send(10 * x).unwrap();
+    };
+    complete(2);
+    hydroflow.tick();
+    assert!(out.borrow().is_empty());
+
+    complete(1);
+    hydroflow.tick();
+    assert_eq!(&[10, 20], &**out.borrow());
+
+    complete(3);
+    hydroflow.tick();
+    assert_eq!(&[10, 20, 30], &**out.borrow());
+}
+
+#[test]
+fn test_output_to_sink() {
+    use futures::channel::mpsc;
+
+    use crate::scheduled::handoff::VecHandoff;
+    use prelude::*;
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (data_send, data) = builder.add_channel_input::<_, Option<u64>, VecHandoff<_>>("data");
+
+    // A channel with no spare capacity, so the sink applies backpressure.
+    let (sink_send, mut sink_recv) = mpsc::channel(0);
+    let output = builder.add_output_to_sink("sink", sink_send);
+    builder.add_subgraph(
+        "main",
+        data.flatten().pull_to_push().map(Some).push_to(output),
+    );
+
+    let mut hydroflow = builder.build();
+
+    for x in [1, 2, 3] {
+        data_send.give(Some(x));
+    }
+    data_send.flush();
+
+    let mut out = Vec::new();
+    for _ in 0..10 {
+        hydroflow.tick();
+        while let Ok(Some(x)) = sink_recv.try_next() {
+            out.push(x);
+        }
+    }
+    assert_eq!(&[1, 2, 3], &*out);
+}
+
+#[test]
+fn test_into_stream() {
+    use futures::StreamExt;
+
+    use crate::scheduled::handoff::VecHandoff;
+    use prelude::*;
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (data_send, data) = builder.add_channel_input::<_, Option<u64>, VecHandoff<_>>("data");
+
+    let stream = data
+        .flatten()
+        .map(|x| 10 * x)
+        .into_stream(&mut builder, "out");
+
+    let mut hydroflow = builder.build();
+
+    for x in [1, 2, 3] {
+        data_send.give(Some(x));
+    }
+    data_send.flush();
+    hydroflow.tick();
+
+    let out = futures::executor::block_on(stream.take(3).collect::<Vec<_>>());
+    assert_eq!(&[10, 20, 30], &*out);
+}
+
+#[test]
+fn test_sort() {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::scheduled::handoff::VecHandoff;
+    use prelude::*;
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (direct_send, direct) =
+        builder.add_channel_input::<_, Option<u64>, VecHandoff<_>>("direct");
+    let (scaled_send, scaled) =
+        builder.add_channel_input::<_, Option<u64>, VecHandoff<_>>("scaled");
+    let (edge_push, edge_pull) = builder.make_edge::<_, VecHandoff<u64>, Option<u64>>("edge");
+
+    let out = Rc::new(RefCell::new(Vec::new()));
+    let out_inner = out.clone();
+
+    // Items on the edge arrive via a second subgraph, so the sort must wait
+    // for the end of the tick to see them alongside the direct input.
+    builder.add_subgraph(
+        "scale",
+        scaled
+            .flatten()
+            .map(|x| Some(10 * x))
+            .pull_to_push()
+            .push_to(edge_push),
+    );
+    builder.add_subgraph(
+        "main",
+        direct
+            .flatten()
+            .chain(edge_pull.flatten())
+            .sort()
+            .pull_to_push()
+            .for_each(move |x| (*out_inner).borrow_mut().push(x)),
+    );
+
+    let mut hydroflow = builder.build();
+
+    for x in [3, 1, 2] {
+        direct_send.give(Some(x));
+        scaled_send.give(Some(x));
+    }
+    direct_send.flush();
+    scaled_send.flush();
+    hydroflow.tick();
+
+    assert_eq!(&[1, 2, 3, 10, 20, 30], &**out.borrow());
+}
+
+#[test]
+fn test_top_k() {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::scheduled::handoff::VecHandoff;
+    use prelude::*;
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (data_send, data) =
+        builder.add_channel_input::<_, Option<(&'static str, u64)>, VecHandoff<_>>("data");
+
+    let out = Rc::new(RefCell::new(Vec::new()));
+    let out_inner = out.clone();
+
+    builder.add_subgraph(
+        "main",
+        data.flatten()
+            .top_k(2, |&(_, score)| score)
+            .pull_to_push()
+            .for_each(move |x| (*out_inner).borrow_mut().push(x)),
+    );
+
+    let mut hydroflow = builder.build();
+
+    for x in [("a", 5), ("b", 9), ("c", 1), ("d", 7)] {
+        data_send.give(Some(x));
+    }
+    data_send.flush();
+    hydroflow.tick();
+
+    assert_eq!(&[("b", 9), ("d", 7)], &**out.borrow());
+
+    data_send.give(Some(("e", 3)));
+    data_send.flush();
+    hydroflow.tick();
+
+    assert_eq!(&[("b", 9), ("d", 7), ("e", 3)], &**out.borrow());
+}
+
+#[test]
+fn test_iterate() {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::scheduled::handoff::VecHandoff;
+    use prelude::*;
+
+    let mut builder = HydroflowBuilder::default();
+
+    let (edges_send, edges) =
+        builder.add_channel_input::<_, Option<(usize, usize)>, VecHandoff<_>>("edges");
+
+    let reached = builder.iterate("reach", [0].into_hydroflow(), move |_builder, reached| {
+        reached
+            .map(|v| (v, ()))
+            .join(edges.flatten())
+            .map(|(_old_v, (), new_v)| new_v)
+    });
+
+    let out = Rc::new(RefCell::new(Vec::new()));
+    let out_inner = out.clone();
+    builder.add_subgraph(
+        "output",
+        reached
+            .flatten()
+            .pull_to_push()
+            .for_each(move |v| (*out_inner).borrow_mut().push(v)),
+    );
+
+    let mut hydroflow = builder.build();
+
+    for edge in [(5, 10), (0, 3), (3, 6), (6, 0)] {
+        edges_send.give(Some(edge));
+    }
+    edges_send.flush();
+    hydroflow.tick();
+
+    let mut reached = (*out).take();
+    reached.sort_unstable();
+    assert_eq!(&[0, 3, 6], &*reached);
+
+    edges_send.give(Some((6, 5)));
+    edges_send.flush();
+    hydroflow.tick();
+
+    let mut reached = (*out).take();
+    reached.sort_unstable();
+    assert_eq!(&[5, 10], &*reached);
+}
+
 #[test]
 fn test_covid() {
     use crate::scheduled::handoff::VecHandoff;
@@ -1,10 +1,21 @@
 use std::{
     borrow::Cow,
-    collections::{hash_map::DefaultHasher, HashMap},
-    hash::Hasher,
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
 };
 
-use crate::{builder::HydroflowBuilder, scheduled::handoff::VecHandoff};
+use crate::{
+    builder::HydroflowBuilder,
+    lang::partitioner::{rendezvous_hash, stable_hash, HashPartitioner, Partitioner},
+    scheduled::{
+        handoff::{
+            handoff_list::{PortList, PortListSplit},
+            VecHandoff,
+        },
+        port::RECV,
+        type_list::Extend,
+    },
+};
 
 use super::{
     flatten::FlattenSurface, pull_chain::ChainPullSurface, pull_handoff::HandoffPullSurface,
@@ -42,6 +53,31 @@ where
         Self: 'static + PullSurface<ItemOut = (Key, Val)>,
         Other: PullSurface<ItemOut = (Key, Val)>;
 
+    /// Like [`Self::exchange`], but assigns keys to participants with
+    /// `partitioner`. Participants are numbered in increasing order of their
+    /// ids in `address_book`, and records which `partitioner` assigns to a
+    /// partition past the last participant are dropped. Panics if
+    /// `address_book` is empty.
+    #[allow(clippy::too_many_arguments)]
+    fn exchange_by<Name, Other, Key, Val, Part>(
+        self,
+        builder: &mut HydroflowBuilder,
+        name: Name,
+        address_book: HashMap<u64, String>,
+        remote_input: Other,
+        my_id: u64,
+        partitioner: Part,
+        outbound_messages: NetworkOut<<Self as BaseSurface>::ItemOut>,
+    ) -> ExchangeSurface<Key, Val, Other>
+    where
+        Name: Into<Cow<'static, str>>,
+        Self: Sized,
+        Key: 'static,
+        Val: 'static,
+        Part: 'static + Partitioner<Key>,
+        Self: 'static + PullSurface<ItemOut = (Key, Val)>,
+        Other: PullSurface<ItemOut = (Key, Val)>;
+
     fn broadcast<Name, Other, T>(
         self,
         builder: &mut HydroflowBuilder,
@@ -56,6 +92,58 @@ where
         T: Eq + Clone,
         Self: 'static + PullSurface<ItemOut = T>,
         Other: PullSurface<ItemOut = T>;
+
+    /// Like [`Self::exchange`], but membership is driven by the
+    /// `membership` stream rather than fixed up front. Keys are assigned to
+    /// the current members by rendezvous hashing, so a join or leave only
+    /// moves the keys gained or lost by that member. Messages for a member
+    /// which has joined but is not yet [`MembershipChange::Ready`] are
+    /// buffered until it is, and records are held while there are no members.
+    /// `my_id` should join like any other member; it is ready immediately.
+    #[allow(clippy::too_many_arguments)]
+    fn exchange_dynamic<Name, Membership, Other, Key, Val>(
+        self,
+        builder: &mut HydroflowBuilder,
+        name: Name,
+        membership: Membership,
+        remote_input: Other,
+        my_id: u64,
+        outbound_messages: NetworkOut<<Self as BaseSurface>::ItemOut>,
+    ) -> ExchangeSurface<Key, Val, Other>
+    where
+        Name: Into<Cow<'static, str>>,
+        Self: Sized,
+        Key: 'static + Hash + Clone,
+        Val: 'static + Clone,
+        Membership: 'static + PullSurface<ItemOut = MembershipChange>,
+        Self: 'static + PullSurface<ItemOut = (Key, Val)>,
+        Other: PullSurface<ItemOut = (Key, Val)>,
+        Membership::InputHandoffs: Extend<Self::InputHandoffs>,
+        <Membership::InputHandoffs as Extend<Self::InputHandoffs>>::Extended: PortList<RECV>
+            + PortListSplit<RECV, Membership::InputHandoffs, Suffix = Self::InputHandoffs>;
+
+    /// Like [`Self::broadcast`], but sends to the current members given by the
+    /// `membership` stream. Messages for members which are not yet ready are
+    /// buffered; members do not receive messages sent before they joined.
+    fn broadcast_dynamic<Name, Membership, Other, T>(
+        self,
+        builder: &mut HydroflowBuilder,
+        name: Name,
+        membership: Membership,
+        remote_input: Other,
+        my_id: u64,
+        outbound_messages: NetworkOut<<Self as BaseSurface>::ItemOut>,
+    ) -> BroadcastSurface<T, Other>
+    where
+        Name: Into<Cow<'static, str>>,
+        Self: Sized,
+        T: 'static + Clone,
+        Membership: 'static + PullSurface<ItemOut = MembershipChange>,
+        Self: 'static + PullSurface<ItemOut = T>,
+        Other: PullSurface<ItemOut = T>,
+        Membership::InputHandoffs: Extend<Self::InputHandoffs>,
+        <Membership::InputHandoffs as Extend<Self::InputHandoffs>>::Extended: PortList<RECV>
+            + PortListSplit<RECV, Membership::InputHandoffs, Suffix = Self::InputHandoffs>;
 }
 
 impl<T> Exchange for T
@@ -79,6 +167,40 @@ where
         Self: 'static + PullSurface<ItemOut = (Key, Val)>,
         Other: PullSurface<ItemOut = (Key, Val)>,
     {
+        self.exchange_by(
+            builder,
+            name,
+            address_book,
+            remote_input,
+            my_id,
+            HashPartitioner,
+            outbound_messages,
+        )
+    }
+
+    fn exchange_by<Name, Other, Key, Val, Part>(
+        self,
+        builder: &mut HydroflowBuilder,
+        name: Name,
+        address_book: HashMap<u64, String>,
+        remote_input: Other,
+        my_id: u64,
+        partitioner: Part,
+        outbound_messages: NetworkOut<<Self as BaseSurface>::ItemOut>,
+    ) -> ExchangeSurface<Key, Val, Other>
+    where
+        Name: Into<Cow<'static, str>>,
+        Self: Sized,
+        Key: 'static,
+        Val: 'static,
+        Part: 'static + Partitioner<Key>,
+        Self: 'static + PullSurface<ItemOut = (Key, Val)>,
+        Other: PullSurface<ItemOut = (Key, Val)>,
+    {
+        assert!(
+            !address_book.is_empty(),
+            "Exchange requires at least one participant in the address book."
+        );
         let name = name.into();
 
         let (local_inputs_send, local_inputs_recv) = builder
@@ -87,29 +209,34 @@ where
                 name
             ));
 
-        let num_participants: u64 = address_book.len().try_into().unwrap();
+        let mut participants: Vec<_> = address_book.into_iter().collect();
+        participants.sort_unstable_by_key(|&(id, _)| id);
+        let my_idx = participants.iter().position(|&(id, _)| id == my_id);
+        let addresses: Vec<_> = participants.into_iter().map(|(_, addr)| addr).collect();
+        let num_participants = addresses.len();
 
         builder.add_subgraph(
             name,
-            IterPullSurface::new(address_book.into_iter())
-                .join(self.map(move |(x, v)| {
-                    // TODO(justin): We should make our own thing here, I don't
-                    // know if it's guaranteed this will be consistent across
-                    // machines? And we might want to implement our own policy
-                    // here.
-                    let mut s = DefaultHasher::new();
-                    x.hash(&mut s);
-                    let hash_val = s.finish();
-                    (hash_val % num_participants, (x, v))
-                }))
-                .pull_to_push()
+            self.pull_to_push()
+                .filter_map(move |(k, v)| {
+                    let idx = partitioner.partition(&k, num_participants);
+                    if idx < num_participants {
+                        Some((idx, (k, v)))
+                    } else {
+                        eprintln!(
+                            "dropping record assigned to partition {} of {}",
+                            idx, num_participants
+                        );
+                        None
+                    }
+                })
                 .partition(
-                    move |&(id, _, _)| id == my_id,
+                    move |&(idx, _)| Some(idx) == my_idx,
                     StartPushSurface::new()
-                        .map(|(_, _, v)| Some(v))
+                        .map(|(_, kv): (usize, _)| Some(kv))
                         .push_to(local_inputs_send),
                     StartPushSurface::new()
-                        .map(|(_id, address, data)| Some((address, data)))
+                        .map(move |(idx, kv): (usize, _)| Some((addresses[idx].clone(), kv)))
                         .push_to(outbound_messages),
                 ),
         );
@@ -154,4 +281,240 @@ where
 
         local_inputs_recv.flatten().chain(remote_input)
     }
+
+    fn exchange_dynamic<Name, Membership, Other, Key, Val>(
+        self,
+        builder: &mut HydroflowBuilder,
+        name: Name,
+        membership: Membership,
+        remote_input: Other,
+        my_id: u64,
+        outbound_messages: NetworkOut<<Self as BaseSurface>::ItemOut>,
+    ) -> ExchangeSurface<Key, Val, Other>
+    where
+        Name: Into<Cow<'static, str>>,
+        Self: Sized,
+        Key: 'static + Hash + Clone,
+        Val: 'static + Clone,
+        Membership: 'static + PullSurface<ItemOut = MembershipChange>,
+        Self: 'static + PullSurface<ItemOut = (Key, Val)>,
+        Other: PullSurface<ItemOut = (Key, Val)>,
+        Membership::InputHandoffs: Extend<Self::InputHandoffs>,
+        <Membership::InputHandoffs as Extend<Self::InputHandoffs>>::Extended: PortList<RECV>
+            + PortListSplit<RECV, Membership::InputHandoffs, Suffix = Self::InputHandoffs>,
+    {
+        let name = name.into();
+
+        let (local_inputs_send, local_inputs_recv) = builder
+            .make_edge::<_, VecHandoff<(Key, Val)>, Option<(Key, Val)>>(format!(
+                "{} handoff",
+                name
+            ));
+
+        let mut address_book = DynamicAddressBook::new(my_id);
+        // Chain membership first so changes apply before this tick's records.
+        let events = membership.map(Err).chain(self.map(Ok));
+
+        builder.add_subgraph(
+            name,
+            events
+                .pull_to_push()
+                .map(move |event: Result<(Key, Val), MembershipChange>| {
+                    let mut out = Vec::new();
+                    let records = match event {
+                        Ok(record) => vec![record],
+                        Err(change) => address_book.apply(change, &mut out),
+                    };
+                    for record in records {
+                        address_book.exchange(record, &mut out);
+                    }
+                    out
+                })
+                .flatten()
+                .partition(
+                    |(address, _): &(Option<String>, _)| address.is_none(),
+                    StartPushSurface::new()
+                        .map(|(_, record)| Some(record))
+                        .push_to(local_inputs_send),
+                    StartPushSurface::new()
+                        .map(|(address, record): (Option<String>, _)| {
+                            Some((address.unwrap(), record))
+                        })
+                        .push_to(outbound_messages),
+                ),
+        );
+
+        local_inputs_recv.flatten().chain(remote_input)
+    }
+
+    fn broadcast_dynamic<Name, Membership, Other, U>(
+        self,
+        builder: &mut HydroflowBuilder,
+        name: Name,
+        membership: Membership,
+        remote_input: Other,
+        my_id: u64,
+        outbound_messages: NetworkOut<<Self as BaseSurface>::ItemOut>,
+    ) -> BroadcastSurface<U, Other>
+    where
+        Name: Into<Cow<'static, str>>,
+        Self: Sized,
+        U: 'static + Clone,
+        Membership: 'static + PullSurface<ItemOut = MembershipChange>,
+        Self: 'static + PullSurface<ItemOut = U>,
+        Other: PullSurface<ItemOut = U>,
+        Membership::InputHandoffs: Extend<Self::InputHandoffs>,
+        <Membership::InputHandoffs as Extend<Self::InputHandoffs>>::Extended: PortList<RECV>
+            + PortListSplit<RECV, Membership::InputHandoffs, Suffix = Self::InputHandoffs>,
+    {
+        let name = name.into();
+
+        let (local_inputs_send, local_inputs_recv) =
+            builder.make_edge::<_, VecHandoff<U>, Option<U>>(format!("{} handoff", name));
+
+        let mut address_book = DynamicAddressBook::new(my_id);
+        let events = membership.map(Err).chain(self.map(Ok));
+
+        builder.add_subgraph(
+            name,
+            events
+                .pull_to_push()
+                .map(move |event: Result<U, MembershipChange>| {
+                    let mut out = Vec::new();
+                    match event {
+                        Ok(item) => address_book.broadcast(item, &mut out),
+                        // Messages buffered for a departed member are dropped.
+                        Err(change) => drop(address_book.apply(change, &mut out)),
+                    }
+                    out
+                })
+                .flatten()
+                .partition(
+                    |(address, _): &(Option<String>, _)| address.is_none(),
+                    StartPushSurface::new()
+                        .map(|(_, item)| Some(item))
+                        .push_to(local_inputs_send),
+                    StartPushSurface::new()
+                        .map(|(address, item): (Option<String>, _)| Some((address.unwrap(), item)))
+                        .push_to(outbound_messages),
+                ),
+        );
+
+        local_inputs_recv.flatten().chain(remote_input)
+    }
+}
+
+/// A change to the set of participants of a dynamic exchange or broadcast.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MembershipChange {
+    /// Member `id` is joining at `address`. Keys are assigned to it right
+    /// away, but messages for it are buffered until it is [`Self::Ready`].
+    Join { id: u64, address: String },
+    /// Member `id` can now receive messages.
+    Ready { id: u64 },
+    /// Member `id` has left. Records buffered for it are re-partitioned.
+    Leave { id: u64 },
+}
+
+struct Member<T> {
+    address: String,
+    ready: bool,
+    buffer: Vec<T>,
+}
+
+/// Membership state for [`Exchange::exchange_dynamic`] and
+/// [`Exchange::broadcast_dynamic`]. Routed messages are pushed to `out` as
+/// `(address, item)` pairs, with `None` meaning the local member.
+struct DynamicAddressBook<T> {
+    my_id: u64,
+    members: BTreeMap<u64, Member<T>>,
+    /// Records held while there are no members to assign them to.
+    unassigned: Vec<T>,
+}
+
+impl<T> DynamicAddressBook<T> {
+    fn new(my_id: u64) -> Self {
+        Self {
+            my_id,
+            members: BTreeMap::new(),
+            unassigned: Vec::new(),
+        }
+    }
+
+    /// Applies `change`, pushing any messages which it releases to `out`.
+    /// Returns the records which must be re-assigned.
+    fn apply(&mut self, change: MembershipChange, out: &mut Vec<(Option<String>, T)>) -> Vec<T> {
+        match change {
+            MembershipChange::Join { id, address } => {
+                let ready = id == self.my_id;
+                let member = self.members.entry(id).or_insert_with(|| Member {
+                    address: String::new(),
+                    ready,
+                    buffer: Vec::new(),
+                });
+                member.address = address;
+                std::mem::take(&mut self.unassigned)
+            }
+            MembershipChange::Ready { id } => {
+                if let Some(member) = self.members.get_mut(&id) {
+                    member.ready = true;
+                    let address = &member.address;
+                    out.extend(
+                        member
+                            .buffer
+                            .drain(..)
+                            .map(|item| (Some(address.clone()), item)),
+                    );
+                }
+                Vec::new()
+            }
+            MembershipChange::Leave { id } => self
+                .members
+                .remove(&id)
+                .map(|member| member.buffer)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn send(&mut self, id: u64, item: T, out: &mut Vec<(Option<String>, T)>) {
+        if id == self.my_id {
+            out.push((None, item));
+            return;
+        }
+        let member = self.members.get_mut(&id).unwrap();
+        if member.ready {
+            out.push((Some(member.address.clone()), item));
+        } else {
+            member.buffer.push(item);
+        }
+    }
+
+    fn broadcast(&mut self, item: T, out: &mut Vec<(Option<String>, T)>)
+    where
+        T: Clone,
+    {
+        let peers: Vec<_> = self
+            .members
+            .keys()
+            .copied()
+            .filter(|&id| id != self.my_id)
+            .collect();
+        for id in peers {
+            self.send(id, item.clone(), out);
+        }
+        out.push((None, item));
+    }
+}
+
+impl<Key, Val> DynamicAddressBook<(Key, Val)>
+where
+    Key: Hash,
+{
+    fn exchange(&mut self, record: (Key, Val), out: &mut Vec<(Option<String>, (Key, Val))>) {
+        let hash = stable_hash(&record.0);
+        match rendezvous_hash(hash, self.members.keys().copied()) {
+            Some(id) => self.send(id, record, out),
+            None => self.unassigned.push(record),
+        }
+    }
 }
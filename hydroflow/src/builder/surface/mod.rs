@@ -8,20 +8,23 @@
 //! use hydroflow::build::prelude::*;
 //! ```
 //!
-//! * [`BaseSurface`] provides linear chaining methods like [`BaseSurface::map`], [`BaseSurface::filter`], etc..
-//! * [`PullSurface`] provides methods to combine multiple input streams: [`PullSurface::chain`], [`PullSurface::join`].
+//! * [`BaseSurface`] provides linear chaining methods like [`BaseSurface::map`], [`BaseSurface::filter`], [`BaseSurface::unique`], etc..
+//! * [`PullSurface`] provides methods to combine multiple input streams: [`PullSurface::chain`], [`PullSurface::merge`], [`PullSurface::join`].
+//!     * End-of-tick blocking operators like [`PullSurface::sort`] and async operators like [`PullSurface::map_async`] are also pull-only.
 //!     * To switch to push, call [`PullSurface::pull_to_push`].
 //! * [`PushSurface`] provides sink chaining methods and methods to split into multiple output streams: [`PushSurface::tee`], [`PushSurface::for_each`].
+//!     * [`PushSurface::tee_n`] and [`PushSurface::demux`] split into any number of outputs, given as a [`tl!`](crate::tl) list.
 //!
 //! For implementation info see [super].
 
-use super::build::{PullBuild, PushBuild};
+use super::build::{PullBuild, PullBuildList, PushBuild, PushBuildList};
 
 pub mod filter;
 pub mod filter_map;
 pub mod flatten;
 pub mod map;
 pub mod pivot;
+pub mod unique;
 
 pub mod pull_batch;
 pub mod pull_chain;
@@ -29,21 +32,37 @@ pub mod pull_cross_join;
 pub mod pull_handoff;
 pub mod pull_iter;
 pub mod pull_join;
+pub mod pull_lattice_join;
+pub mod pull_map_async;
+pub mod pull_merge;
+pub mod pull_sort;
+pub mod pull_zip;
 
+pub mod push_demux;
 pub mod push_for_each;
 pub mod push_handoff;
 pub mod push_partition;
 pub mod push_pivot;
 pub mod push_start;
 pub mod push_tee;
+pub mod push_tee_n;
 
 pub mod exchange;
 
+use std::borrow::Cow;
+use std::future::Future;
 use std::hash::Hash;
 
+use futures::channel::mpsc::UnboundedReceiver;
+
+use super::{CollectHandle, HydroflowBuilder};
+
+use crate::lang::event_time::{Event, JoinInput, JoinSide, Timestamp, TumblingWindows, WindowJoin};
+use crate::lang::lattice::causal::{CausalBuffer, VectorClock};
+use crate::lang::lattice::{Convert, LatticeRepr, Merge};
 use crate::scheduled::handoff::handoff_list::{PortList, PortListSplit};
 use crate::scheduled::port::{RECV, SEND};
-use crate::scheduled::type_list::Extend;
+use crate::scheduled::type_list::{Extend, TypeList};
 
 /// Common trait shared between push and pull surface APIs.
 ///
@@ -92,6 +111,26 @@ pub trait BaseSurface {
         filter_map::FilterMapSurface::new(self, func)
     }
 
+    /// Removes duplicate items. Items are remembered across all ticks, so
+    /// each distinct item is only ever emitted once.
+    fn unique(self) -> unique::UniqueSurface<Self>
+    where
+        Self: Sized,
+        Self::ItemOut: Eq + Hash + Clone,
+    {
+        unique::UniqueSurface::new(self, false)
+    }
+
+    /// Removes duplicate items within each tick. Items are forgotten at the
+    /// start of the next tick, so they may be emitted again then.
+    fn unique_per_tick(self) -> unique::UniqueSurface<Self>
+    where
+        Self: Sized,
+        Self::ItemOut: Eq + Hash + Clone,
+    {
+        unique::UniqueSurface::new(self, true)
+    }
+
     fn inspect<Func>(self, mut func: Func) -> map::MapSurface<Self, InspectMapFunc<Self, Func>>
     where
         Self: Sized,
@@ -102,10 +141,104 @@ pub trait BaseSurface {
             item
         })
     }
+
+    /// Folds a stream of `Delta` lattice deltas into a running `Lr` state,
+    /// emitting the new state each time it changes. `Delta` is usually the
+    /// same representation as `Lr`.
+    fn merge_into<Lr, Delta>(
+        self,
+    ) -> filter_map::FilterMapSurface<Self, MergeIntoFunc<Self, Lr, Delta>>
+    where
+        Self: Sized + BaseSurface<ItemOut = Delta::Repr>,
+        Lr: LatticeRepr<Lattice = Delta::Lattice> + Merge<Delta>,
+        Delta: LatticeRepr + Convert<Lr>,
+    {
+        let mut state: Option<Lr::Repr> = None;
+        self.filter_map(move |delta| match &mut state {
+            Some(state) => Lr::merge(state, delta).then(|| state.clone()),
+            None => Some(state.insert(Delta::convert(delta)).clone()),
+        })
+    }
+
+    /// Emits the first item satisfying `pred`, then nothing afterwards. Meant
+    /// for monotone streams (e.g. the output of [`Self::merge_into`]), where
+    /// once the bound is crossed it stays crossed.
+    fn threshold<Func>(
+        self,
+        mut pred: Func,
+    ) -> filter::FilterSurface<Self, ThresholdFunc<Self, Func>>
+    where
+        Self: Sized,
+        Func: FnMut(&Self::ItemOut) -> bool,
+    {
+        let mut fired = false;
+        self.filter(move |item| {
+            if fired || !pred(item) {
+                return false;
+            }
+            fired = true;
+            true
+        })
+    }
+
+    /// Groups an event-time stream into tumbling windows of `size`, emitting
+    /// each window's items once the watermark passes its end. See
+    /// [`TumblingWindows`].
+    fn window<T>(
+        self,
+        size: Timestamp,
+    ) -> flatten::FlattenSurface<map::MapSurface<Self, WindowFunc<Self, T>>>
+    where
+        Self: Sized + BaseSurface<ItemOut = Event<T>>,
+    {
+        let mut windows = TumblingWindows::new(size);
+        self.flat_map(move |event| windows.push(event))
+    }
+
+    /// Delivers `(sender, clock, msg)` messages in causal order, holding each
+    /// until the messages it depends on have been delivered. See
+    /// [`CausalBuffer`].
+    fn causal_delivery<Key, T>(
+        self,
+    ) -> flatten::FlattenSurface<map::MapSurface<Self, CausalDeliveryFunc<Self, Key, T>>>
+    where
+        Self: Sized + BaseSurface<ItemOut = (Key, VectorClock<Key>, T)>,
+        Key: Eq + Hash + Clone,
+    {
+        let mut buffer = CausalBuffer::default();
+        self.flat_map(move |(sender, clock, msg)| buffer.push(sender, clock, msg))
+    }
 }
 
 pub type InspectMapFunc<Prev: BaseSurface, Func> = impl FnMut(Prev::ItemOut) -> Prev::ItemOut;
 
+pub type CollectFunc<Prev: BaseSurface> = impl FnMut(Prev::ItemOut);
+
+pub type MergeIntoFunc<
+    Prev: BaseSurface<ItemOut = Delta::Repr>,
+    Lr: LatticeRepr<Lattice = Delta::Lattice> + Merge<Delta>,
+    Delta: LatticeRepr + Convert<Lr>,
+> = impl FnMut(Delta::Repr) -> Option<Lr::Repr>;
+pub type ThresholdFunc<Prev: BaseSurface, Func> = impl FnMut(&Prev::ItemOut) -> bool;
+pub type WindowFunc<Prev: BaseSurface<ItemOut = Event<T>>, T> =
+    impl FnMut(Event<T>) -> Vec<Event<Vec<T>>>;
+pub type CausalDeliveryFunc<
+    Prev: BaseSurface<ItemOut = (Key, VectorClock<Key>, T)>,
+    Key: Eq + Hash + Clone,
+    T,
+> = impl FnMut((Key, VectorClock<Key>, T)) -> Vec<(Key, VectorClock<Key>, T)>;
+pub type WindowJoinFunc<
+    Prev: PullSurface,
+    Other: PullSurface,
+    Key: Eq + Hash + Clone,
+    ValSelf: Clone,
+    ValOther: Clone,
+> = impl FnMut(JoinInput<Key, ValSelf, ValOther>) -> Vec<Event<(Key, ValSelf, ValOther)>>;
+
+pub type SortFunc<Prev: PullSurface> = impl FnMut(&mut Vec<Prev::ItemOut>);
+pub type SortByKeyFunc<Prev: PullSurface, Func, Key> = impl FnMut(&mut Vec<Prev::ItemOut>);
+pub type TopKFunc<Prev: PullSurface, Func, Key> = impl FnMut(&mut Vec<Prev::ItemOut>);
+
 pub trait PullSurface: BaseSurface {
     type InputHandoffs: PortList<RECV>;
     type Build: PullBuild<InputHandoffs = Self::InputHandoffs, ItemOut = Self::ItemOut>;
@@ -124,6 +257,20 @@ pub trait PullSurface: BaseSurface {
         pull_chain::ChainPullSurface::new(self, other)
     }
 
+    /// Merges `self` with a [`tl!`](crate::tl) list of other surfaces,
+    /// taking one item from each input in turn rather than draining them one
+    /// after another like [`PullSurface::chain`].
+    fn merge<Others>(
+        self,
+        others: Others,
+    ) -> pull_merge::MergePullSurface<(Self, Others), Self::ItemOut>
+    where
+        Self: Sized,
+        (Self, Others): PullSurfaceList<Self::ItemOut>,
+    {
+        pull_merge::MergePullSurface::new((self, others))
+    }
+
     fn join<Other, Key, ValSelf, ValOther>(
         self,
         other: Other,
@@ -142,6 +289,38 @@ pub trait PullSurface: BaseSurface {
         pull_join::JoinPullSurface::new(self, other)
     }
 
+    /// Joins two keyed streams of lattice deltas, merging each side's
+    /// `DeltaSelf`/`DeltaOther` deltas into a per-key `LrSelf`/`LrOther`
+    /// value, i.e. a pair of `MapUnionRepr` states. Emits `(key, self_value,
+    /// other_value)` whenever either side's value for a key grows and both
+    /// sides have a value for it.
+    fn lattice_join<LrSelf, DeltaSelf, LrOther, DeltaOther, Other, Key>(
+        self,
+        other: Other,
+    ) -> pull_lattice_join::LatticeJoinPullSurface<
+        Self,
+        Other,
+        LrSelf,
+        DeltaSelf,
+        LrOther,
+        DeltaOther,
+    >
+    where
+        Self: Sized + PullSurface<ItemOut = (Key, DeltaSelf::Repr)>,
+        Other: PullSurface<ItemOut = (Key, DeltaOther::Repr)>,
+        Key: 'static + Eq + Hash + Clone,
+        LrSelf: 'static + LatticeRepr<Lattice = DeltaSelf::Lattice> + Merge<DeltaSelf>,
+        DeltaSelf: 'static + LatticeRepr + Convert<LrSelf>,
+        LrOther: 'static + LatticeRepr<Lattice = DeltaOther::Lattice> + Merge<DeltaOther>,
+        DeltaOther: 'static + LatticeRepr + Convert<LrOther>,
+
+        Self::InputHandoffs: Extend<Other::InputHandoffs>,
+        <Self::InputHandoffs as Extend<Other::InputHandoffs>>::Extended: PortList<RECV>
+            + PortListSplit<RECV, Self::InputHandoffs, Suffix = Other::InputHandoffs>,
+    {
+        pull_lattice_join::LatticeJoinPullSurface::new(self, other)
+    }
+
     fn batch_with<Other, Key, ValSelf, ValOther>(
         self,
         other: Other,
@@ -160,6 +339,49 @@ pub trait PullSurface: BaseSurface {
         pull_batch::BatchPullSurface::new(self, other)
     }
 
+    /// Joins two keyed event-time streams, matching items with the same key
+    /// whose times fall in the same tumbling window of `size`. A window's
+    /// matches are emitted once the watermarks of both inputs pass its end.
+    /// See [`WindowJoin`].
+    #[allow(clippy::type_complexity)]
+    fn window_join<Other, Key, ValSelf, ValOther>(
+        self,
+        other: Other,
+        size: Timestamp,
+    ) -> flatten::FlattenSurface<
+        map::MapSurface<
+            pull_chain::ChainPullSurface<
+                map::MapSurface<
+                    Self,
+                    fn(Event<(Key, ValSelf)>) -> JoinInput<Key, ValSelf, ValOther>,
+                >,
+                map::MapSurface<
+                    Other,
+                    fn(Event<(Key, ValOther)>) -> JoinInput<Key, ValSelf, ValOther>,
+                >,
+            >,
+            WindowJoinFunc<Self, Other, Key, ValSelf, ValOther>,
+        >,
+    >
+    where
+        Self: Sized + PullSurface<ItemOut = Event<(Key, ValSelf)>>,
+        Other: PullSurface<ItemOut = Event<(Key, ValOther)>>,
+        Key: 'static + Eq + Hash + Clone,
+        ValSelf: 'static + Clone,
+        ValOther: 'static + Clone,
+
+        Self::InputHandoffs: Extend<Other::InputHandoffs>,
+        <Self::InputHandoffs as Extend<Other::InputHandoffs>>::Extended: PortList<RECV>
+            + PortListSplit<RECV, Self::InputHandoffs, Suffix = Other::InputHandoffs>,
+    {
+        let mut join = WindowJoin::new(size);
+        let left: fn(_) -> _ = JoinSide::Left;
+        let right: fn(_) -> _ = JoinSide::Right;
+        self.map(left)
+            .chain(other.map(right))
+            .flat_map(move |event| join.push(event))
+    }
+
     fn cross_join<Other>(self, other: Other) -> pull_cross_join::CrossJoinPullSurface<Self, Other>
     where
         Self: Sized + PullSurface,
@@ -174,6 +396,139 @@ pub trait PullSurface: BaseSurface {
         pull_cross_join::CrossJoinPullSurface::new(self, other)
     }
 
+    /// Pairs up items from `self` and `other` by position. If one side has
+    /// more items than the other, the excess is kept and paired with items
+    /// arriving on the other side in later runs or ticks.
+    fn zip<Other>(self, other: Other) -> pull_zip::ZipPullSurface<Self, Other>
+    where
+        Self: Sized + PullSurface,
+        Other: PullSurface,
+        Self::ItemOut: 'static,
+        Other::ItemOut: 'static,
+
+        Self::InputHandoffs: Extend<Other::InputHandoffs>,
+        <Self::InputHandoffs as Extend<Other::InputHandoffs>>::Extended: PortList<RECV>
+            + PortListSplit<RECV, Self::InputHandoffs, Suffix = Other::InputHandoffs>,
+    {
+        pull_zip::ZipPullSurface::new(self, other)
+    }
+
+    /// Maps each item to a future and emits the futures' outputs in input
+    /// order. Up to `limit` futures are run concurrently; the subgraph is
+    /// woken up when they make progress.
+    ///
+    /// Only available on pull surfaces, not on [`BaseSurface`]: finished
+    /// outputs are emitted when the subgraph is woken, which may be with no
+    /// new input, and a push chain only runs when an item is pushed into it.
+    /// Call this before [`PullSurface::pull_to_push`].
+    fn map_async<Func, Fut>(
+        self,
+        limit: usize,
+        func: Func,
+    ) -> pull_map_async::MapAsyncPullSurface<Self, Func>
+    where
+        Self: Sized,
+        Self::ItemOut: 'static,
+        Func: 'static + FnMut(Self::ItemOut) -> Fut,
+        Fut: 'static + Future,
+    {
+        pull_map_async::MapAsyncPullSurface::new(self, func, limit, true)
+    }
+
+    /// Like [`PullSurface::map_async`], but emits outputs as soon as each
+    /// future finishes, regardless of input order.
+    fn map_async_unordered<Func, Fut>(
+        self,
+        limit: usize,
+        func: Func,
+    ) -> pull_map_async::MapAsyncPullSurface<Self, Func>
+    where
+        Self: Sized,
+        Self::ItemOut: 'static,
+        Func: 'static + FnMut(Self::ItemOut) -> Fut,
+        Fut: 'static + Future,
+    {
+        pull_map_async::MapAsyncPullSurface::new(self, func, limit, false)
+    }
+
+    /// Maps each item to a future and emits the outputs one at a time, in
+    /// order. Equivalent to [`PullSurface::map_async`] with a limit of one.
+    fn then<Func, Fut>(self, func: Func) -> pull_map_async::MapAsyncPullSurface<Self, Func>
+    where
+        Self: Sized,
+        Self::ItemOut: 'static,
+        Func: 'static + FnMut(Self::ItemOut) -> Fut,
+        Fut: 'static + Future,
+    {
+        self.map_async(1, func)
+    }
+
+    /// Buffers the tick's input and emits it in sorted order at the end of
+    /// the tick.
+    fn sort(self) -> pull_sort::SortPullSurface<Self, SortFunc<Self>>
+    where
+        Self: Sized,
+        Self::ItemOut: 'static + Ord,
+    {
+        pull_sort::SortPullSurface::new(self, |buffer| buffer.sort())
+    }
+
+    /// Buffers the tick's input and emits it sorted by the key returned by
+    /// `func` at the end of the tick. The sort is stable.
+    fn sort_by_key<Func, Key>(
+        self,
+        mut func: Func,
+    ) -> pull_sort::SortPullSurface<Self, SortByKeyFunc<Self, Func, Key>>
+    where
+        Self: Sized,
+        Self::ItemOut: 'static,
+        Func: FnMut(&Self::ItemOut) -> Key,
+        Key: Ord,
+    {
+        pull_sort::SortPullSurface::new(self, move |buffer| buffer.sort_by_key(&mut func))
+    }
+
+    /// Buffers the tick's input and, at the end of the tick, emits the `k`
+    /// items with the largest keys returned by `func`, largest first.
+    fn top_k<Func, Key>(
+        self,
+        k: usize,
+        mut func: Func,
+    ) -> pull_sort::SortPullSurface<Self, TopKFunc<Self, Func, Key>>
+    where
+        Self: Sized,
+        Self::ItemOut: 'static,
+        Func: FnMut(&Self::ItemOut) -> Key,
+        Key: Ord,
+    {
+        pull_sort::SortPullSurface::new(self, move |buffer| {
+            let mut cmp = |a: &Self::ItemOut, b: &Self::ItemOut| (func)(b).cmp(&(func)(a));
+            if k < buffer.len() {
+                buffer.select_nth_unstable_by(k, &mut cmp);
+                buffer.truncate(k);
+            }
+            buffer.sort_by(cmp);
+        })
+    }
+
+    /// Adds a subgraph which sends this surface's output into the returned
+    /// stream, for consuming Hydroflow outputs from ordinary async code.
+    fn into_stream<Name>(
+        self,
+        builder: &mut HydroflowBuilder,
+        name: Name,
+    ) -> UnboundedReceiver<Self::ItemOut>
+    where
+        Self: 'static + Sized,
+        Name: Into<Cow<'static, str>>,
+    {
+        let name = name.into();
+        let (send, recv) = futures::channel::mpsc::unbounded();
+        let output = builder.add_output_to_sink(format!("{} sink", name), send);
+        builder.add_subgraph(name, self.pull_to_push().map(Some).push_to(output));
+        recv
+    }
+
     fn pull_to_push(self) -> push_pivot::PivotPushSurface<Self>
     where
         Self: Sized,
@@ -224,6 +579,24 @@ pub trait PushSurface: BaseSurface {
         self.push_to(next)
     }
 
+    /// Collects all items into the returned [`CollectHandle`], which can be
+    /// read between ticks.
+    #[allow(clippy::type_complexity)]
+    fn collect_into(
+        self,
+    ) -> (
+        Self::Output<push_for_each::ForEachPushSurfaceReversed<CollectFunc<Self>, Self::ItemOut>>,
+        CollectHandle<Self::ItemOut>,
+    )
+    where
+        Self: Sized,
+    {
+        let handle = CollectHandle::new();
+        let handle_inner = handle.clone();
+        let output = self.for_each(move |item| handle_inner.push(item));
+        (output, handle)
+    }
+
     fn partition<Func, NextA, NextB>(
         self,
         func: Func,
@@ -243,6 +616,38 @@ pub trait PushSurface: BaseSurface {
         let next = push_partition::PartitionPushSurfaceReversed::new(func, next_a, next_b);
         self.push_to(next)
     }
+
+    /// Sends a copy of each item to every output in `nexts`, a [`tl!`](crate::tl)
+    /// list of surfaces started with [`HydroflowBuilder::start_tee()`](crate::builder::HydroflowBuilder::start_tee).
+    fn tee_n<Nexts>(
+        self,
+        nexts: Nexts,
+    ) -> Self::Output<push_tee_n::TeeNPushSurfaceReversed<Nexts, Self::ItemOut>>
+    where
+        Self: Sized,
+        Self::ItemOut: Clone,
+        Nexts: PushSurfaceReversedList<Self::ItemOut>,
+    {
+        let next = push_tee_n::TeeNPushSurfaceReversed::new(nexts);
+        self.push_to(next)
+    }
+
+    /// Sends each item to the output in `nexts` at the index returned by
+    /// `func`. Items with an out-of-range index are dropped. To route by enum
+    /// variant, return the variant's discriminant, e.g. `|x| x.kind as usize`.
+    fn demux<Func, Nexts>(
+        self,
+        func: Func,
+        nexts: Nexts,
+    ) -> Self::Output<push_demux::DemuxPushSurfaceReversed<Nexts, Func, Self::ItemOut>>
+    where
+        Self: Sized,
+        Func: Fn(&Self::ItemOut) -> usize,
+        Nexts: PushSurfaceReversedList<Self::ItemOut>,
+    {
+        let next = push_demux::DemuxPushSurfaceReversed::new(func, nexts);
+        self.push_to(next)
+    }
 }
 
 /// This extra layer is needed due to the ownership order. In the functional
@@ -259,3 +664,75 @@ pub trait PushSurfaceReversed {
 
     fn into_parts(self) -> (Self::OutputHandoffs, Self::Build);
 }
+
+/// A variadic list of [`PullSurface`]s which all yield the same `T`, for N-ary
+/// pull operators like [`PullSurface::merge`].
+pub trait PullSurfaceList<T>: TypeList {
+    type InputHandoffs: PortList<RECV>;
+    type Build: PullBuildList<T, InputHandoffs = Self::InputHandoffs>;
+
+    fn into_parts(self) -> (Self::InputHandoffs, Self::Build);
+}
+impl<T, Prev, Rest> PullSurfaceList<T> for (Prev, Rest)
+where
+    Prev: PullSurface<ItemOut = T>,
+    Rest: PullSurfaceList<T>,
+
+    Prev::InputHandoffs: Extend<Rest::InputHandoffs>,
+    <Prev::InputHandoffs as Extend<Rest::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, Prev::InputHandoffs, Suffix = Rest::InputHandoffs>,
+{
+    type InputHandoffs = <Prev::InputHandoffs as Extend<Rest::InputHandoffs>>::Extended;
+    type Build = (Prev::Build, Rest::Build);
+
+    fn into_parts(self) -> (Self::InputHandoffs, Self::Build) {
+        let (prev, rest) = self;
+        let (connect_prev, build_prev) = prev.into_parts();
+        let (connect_rest, build_rest) = rest.into_parts();
+        (connect_prev.extend(connect_rest), (build_prev, build_rest))
+    }
+}
+impl<T> PullSurfaceList<T> for () {
+    type InputHandoffs = ();
+    type Build = ();
+
+    fn into_parts(self) -> (Self::InputHandoffs, Self::Build) {
+        ((), ())
+    }
+}
+
+/// A variadic list of [`PushSurfaceReversed`]s which all accept the same `T`,
+/// for N-ary push operators like [`PushSurface::tee_n`].
+pub trait PushSurfaceReversedList<T>: TypeList {
+    type OutputHandoffs: PortList<SEND>;
+    type Build: PushBuildList<T, OutputHandoffs = Self::OutputHandoffs>;
+
+    fn into_parts(self) -> (Self::OutputHandoffs, Self::Build);
+}
+impl<T, Next, Rest> PushSurfaceReversedList<T> for (Next, Rest)
+where
+    Next: PushSurfaceReversed<ItemIn = T>,
+    Rest: PushSurfaceReversedList<T>,
+
+    Next::OutputHandoffs: Extend<Rest::OutputHandoffs>,
+    <Next::OutputHandoffs as Extend<Rest::OutputHandoffs>>::Extended:
+        PortList<SEND> + PortListSplit<SEND, Next::OutputHandoffs, Suffix = Rest::OutputHandoffs>,
+{
+    type OutputHandoffs = <Next::OutputHandoffs as Extend<Rest::OutputHandoffs>>::Extended;
+    type Build = (Next::Build, Rest::Build);
+
+    fn into_parts(self) -> (Self::OutputHandoffs, Self::Build) {
+        let (next, rest) = self;
+        let (connect_next, build_next) = next.into_parts();
+        let (connect_rest, build_rest) = rest.into_parts();
+        (connect_next.extend(connect_rest), (build_next, build_rest))
+    }
+}
+impl<T> PushSurfaceReversedList<T> for () {
+    type OutputHandoffs = ();
+    type Build = ();
+
+    fn into_parts(self) -> (Self::OutputHandoffs, Self::Build) {
+        ((), ())
+    }
+}
@@ -0,0 +1,92 @@
+use super::{BaseSurface, PullSurface};
+
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::builder::build::pull_lattice_join::LatticeJoinPullBuild;
+use crate::lang::lattice::{Convert, LatticeRepr, Merge};
+use crate::scheduled::handoff::handoff_list::{PortList, PortListSplit};
+use crate::scheduled::port::RECV;
+use crate::scheduled::type_list::Extend;
+
+pub struct LatticeJoinPullSurface<PrevA, PrevB, LrA, DeltaA, LrB, DeltaB>
+where
+    PrevA: PullSurface,
+    PrevB: PullSurface,
+
+    PrevA::InputHandoffs: Extend<PrevB::InputHandoffs>,
+    <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, PrevA::InputHandoffs, Suffix = PrevB::InputHandoffs>,
+{
+    prev_a: PrevA,
+    prev_b: PrevB,
+    _phantom: PhantomData<fn(LrA, DeltaA, LrB, DeltaB)>,
+}
+impl<PrevA, PrevB, Key, LrA, DeltaA, LrB, DeltaB>
+    LatticeJoinPullSurface<PrevA, PrevB, LrA, DeltaA, LrB, DeltaB>
+where
+    PrevA: PullSurface<ItemOut = (Key, DeltaA::Repr)>,
+    PrevB: PullSurface<ItemOut = (Key, DeltaB::Repr)>,
+    Key: 'static + Eq + Hash + Clone,
+    LrA: 'static + LatticeRepr<Lattice = DeltaA::Lattice> + Merge<DeltaA>,
+    DeltaA: 'static + LatticeRepr + Convert<LrA>,
+    LrB: 'static + LatticeRepr<Lattice = DeltaB::Lattice> + Merge<DeltaB>,
+    DeltaB: 'static + LatticeRepr + Convert<LrB>,
+
+    PrevA::InputHandoffs: Extend<PrevB::InputHandoffs>,
+    <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, PrevA::InputHandoffs, Suffix = PrevB::InputHandoffs>,
+{
+    pub fn new(prev_a: PrevA, prev_b: PrevB) -> Self {
+        Self {
+            prev_a,
+            prev_b,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<PrevA, PrevB, Key, LrA, DeltaA, LrB, DeltaB> BaseSurface
+    for LatticeJoinPullSurface<PrevA, PrevB, LrA, DeltaA, LrB, DeltaB>
+where
+    PrevA: PullSurface<ItemOut = (Key, DeltaA::Repr)>,
+    PrevB: PullSurface<ItemOut = (Key, DeltaB::Repr)>,
+    Key: 'static + Eq + Hash + Clone,
+    LrA: 'static + LatticeRepr<Lattice = DeltaA::Lattice> + Merge<DeltaA>,
+    DeltaA: 'static + LatticeRepr + Convert<LrA>,
+    LrB: 'static + LatticeRepr<Lattice = DeltaB::Lattice> + Merge<DeltaB>,
+    DeltaB: 'static + LatticeRepr + Convert<LrB>,
+
+    PrevA::InputHandoffs: Extend<PrevB::InputHandoffs>,
+    <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, PrevA::InputHandoffs, Suffix = PrevB::InputHandoffs>,
+{
+    type ItemOut = (Key, LrA::Repr, LrB::Repr);
+}
+
+impl<PrevA, PrevB, Key, LrA, DeltaA, LrB, DeltaB> PullSurface
+    for LatticeJoinPullSurface<PrevA, PrevB, LrA, DeltaA, LrB, DeltaB>
+where
+    PrevA: PullSurface<ItemOut = (Key, DeltaA::Repr)>,
+    PrevB: PullSurface<ItemOut = (Key, DeltaB::Repr)>,
+    Key: 'static + Eq + Hash + Clone,
+    LrA: 'static + LatticeRepr<Lattice = DeltaA::Lattice> + Merge<DeltaA>,
+    DeltaA: 'static + LatticeRepr + Convert<LrA>,
+    LrB: 'static + LatticeRepr<Lattice = DeltaB::Lattice> + Merge<DeltaB>,
+    DeltaB: 'static + LatticeRepr + Convert<LrB>,
+
+    PrevA::InputHandoffs: Extend<PrevB::InputHandoffs>,
+    <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended:
+        PortList<RECV> + PortListSplit<RECV, PrevA::InputHandoffs, Suffix = PrevB::InputHandoffs>,
+{
+    type InputHandoffs = <PrevA::InputHandoffs as Extend<PrevB::InputHandoffs>>::Extended;
+    type Build = LatticeJoinPullBuild<PrevA::Build, PrevB::Build, Key, LrA, DeltaA, LrB, DeltaB>;
+
+    fn into_parts(self) -> (Self::InputHandoffs, Self::Build) {
+        let (connect_a, build_a) = self.prev_a.into_parts();
+        let (connect_b, build_b) = self.prev_b.into_parts();
+        let connect = connect_a.extend(connect_b);
+        let build = LatticeJoinPullBuild::new(build_a, build_b);
+        (connect, build)
+    }
+}
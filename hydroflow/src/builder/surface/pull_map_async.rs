@@ -0,0 +1,56 @@
+use super::{BaseSurface, PullSurface};
+
+use std::future::Future;
+
+use crate::builder::build::pull_map_async::MapAsyncPullBuild;
+
+pub struct MapAsyncPullSurface<Prev, Func>
+where
+    Prev: PullSurface,
+{
+    prev: Prev,
+    func: Func,
+    limit: usize,
+    ordered: bool,
+}
+impl<Prev, Func, Fut> MapAsyncPullSurface<Prev, Func>
+where
+    Prev: PullSurface,
+    Func: FnMut(Prev::ItemOut) -> Fut,
+    Fut: Future,
+{
+    pub fn new(prev: Prev, func: Func, limit: usize, ordered: bool) -> Self {
+        Self {
+            prev,
+            func,
+            limit,
+            ordered,
+        }
+    }
+}
+
+impl<Prev, Func, Fut> BaseSurface for MapAsyncPullSurface<Prev, Func>
+where
+    Prev: PullSurface,
+    Func: FnMut(Prev::ItemOut) -> Fut,
+    Fut: Future,
+{
+    type ItemOut = Fut::Output;
+}
+
+impl<Prev, Func, Fut> PullSurface for MapAsyncPullSurface<Prev, Func>
+where
+    Prev: PullSurface,
+    Prev::ItemOut: 'static,
+    Func: 'static + FnMut(Prev::ItemOut) -> Fut,
+    Fut: 'static + Future,
+{
+    type InputHandoffs = Prev::InputHandoffs;
+    type Build = MapAsyncPullBuild<Prev::Build, Func, Fut>;
+
+    fn into_parts(self) -> (Self::InputHandoffs, Self::Build) {
+        let (connect, build) = self.prev.into_parts();
+        let build = MapAsyncPullBuild::new(build, self.func, self.limit, self.ordered);
+        (connect, build)
+    }
+}
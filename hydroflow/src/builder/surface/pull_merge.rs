@@ -0,0 +1,44 @@
+use super::{BaseSurface, PullSurface, PullSurfaceList};
+
+use std::marker::PhantomData;
+
+use crate::builder::build::pull_merge::MergePullBuild;
+
+pub struct MergePullSurface<Prevs, T>
+where
+    Prevs: PullSurfaceList<T>,
+{
+    prevs: Prevs,
+    _phantom: PhantomData<fn() -> T>,
+}
+impl<Prevs, T> MergePullSurface<Prevs, T>
+where
+    Prevs: PullSurfaceList<T>,
+{
+    pub fn new(prevs: Prevs) -> Self {
+        Self {
+            prevs,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<Prevs, T> BaseSurface for MergePullSurface<Prevs, T>
+where
+    Prevs: PullSurfaceList<T>,
+{
+    type ItemOut = T;
+}
+
+impl<Prevs, T> PullSurface for MergePullSurface<Prevs, T>
+where
+    Prevs: PullSurfaceList<T>,
+{
+    type InputHandoffs = Prevs::InputHandoffs;
+    type Build = MergePullBuild<Prevs::Build, T>;
+
+    fn into_parts(self) -> (Self::InputHandoffs, Self::Build) {
+        let (connect, build) = self.prevs.into_parts();
+        (connect, MergePullBuild::new(build))
+    }
+}
@@ -0,0 +1,49 @@
+use super::{BaseSurface, PullSurface};
+
+use crate::builder::build::pull_sort::SortPullBuild;
+
+/// Buffers all of a tick's input, then calls `Func` on the buffer at the end
+/// of the tick before emitting its contents in order.
+pub struct SortPullSurface<Prev, Func>
+where
+    Prev: PullSurface,
+    Prev::ItemOut: 'static,
+{
+    prev: Prev,
+    func: Func,
+}
+impl<Prev, Func> SortPullSurface<Prev, Func>
+where
+    Prev: PullSurface,
+    Prev::ItemOut: 'static,
+    Func: FnMut(&mut Vec<Prev::ItemOut>),
+{
+    pub fn new(prev: Prev, func: Func) -> Self {
+        Self { prev, func }
+    }
+}
+
+impl<Prev, Func> BaseSurface for SortPullSurface<Prev, Func>
+where
+    Prev: PullSurface,
+    Prev::ItemOut: 'static,
+    Func: FnMut(&mut Vec<Prev::ItemOut>),
+{
+    type ItemOut = Prev::ItemOut;
+}
+
+impl<Prev, Func> PullSurface for SortPullSurface<Prev, Func>
+where
+    Prev: PullSurface,
+    Prev::ItemOut: 'static,
+    Func: FnMut(&mut Vec<Prev::ItemOut>),
+{
+    type InputHandoffs = Prev::InputHandoffs;
+    type Build = SortPullBuild<Prev::Build, Func>;
+
+    fn into_parts(self) -> (Self::InputHandoffs, Self::Build) {
+        let (connect, build) = self.prev.into_parts();
+        let build = SortPullBuild::new(build, self.func);
+        (connect, build)
+    }
+}
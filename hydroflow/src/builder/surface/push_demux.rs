@@ -0,0 +1,44 @@
+use super::{PushSurfaceReversed, PushSurfaceReversedList};
+
+use std::marker::PhantomData;
+
+use crate::builder::build::push_demux::DemuxPushBuild;
+
+pub struct DemuxPushSurfaceReversed<Nexts, Func, T>
+where
+    Nexts: PushSurfaceReversedList<T>,
+    Func: Fn(&T) -> usize,
+{
+    func: Func,
+    nexts: Nexts,
+    _phantom: PhantomData<fn(T)>,
+}
+impl<Nexts, Func, T> DemuxPushSurfaceReversed<Nexts, Func, T>
+where
+    Nexts: PushSurfaceReversedList<T>,
+    Func: Fn(&T) -> usize,
+{
+    pub fn new(func: Func, nexts: Nexts) -> Self {
+        Self {
+            func,
+            nexts,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<Nexts, Func, T> PushSurfaceReversed for DemuxPushSurfaceReversed<Nexts, Func, T>
+where
+    Nexts: PushSurfaceReversedList<T>,
+    Func: Fn(&T) -> usize,
+{
+    type ItemIn = T;
+
+    type OutputHandoffs = Nexts::OutputHandoffs;
+    type Build = DemuxPushBuild<Nexts::Build, Func, T>;
+
+    fn into_parts(self) -> (Self::OutputHandoffs, Self::Build) {
+        let (connect, build) = self.nexts.into_parts();
+        (connect, DemuxPushBuild::new(self.func, build))
+    }
+}
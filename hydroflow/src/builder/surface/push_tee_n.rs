@@ -0,0 +1,42 @@
+use super::{PushSurfaceReversed, PushSurfaceReversedList};
+
+use std::marker::PhantomData;
+
+use crate::builder::build::push_tee_n::TeeNPushBuild;
+
+pub struct TeeNPushSurfaceReversed<Nexts, T>
+where
+    Nexts: PushSurfaceReversedList<T>,
+    T: Clone,
+{
+    nexts: Nexts,
+    _phantom: PhantomData<fn(T)>,
+}
+impl<Nexts, T> TeeNPushSurfaceReversed<Nexts, T>
+where
+    Nexts: PushSurfaceReversedList<T>,
+    T: Clone,
+{
+    pub fn new(nexts: Nexts) -> Self {
+        Self {
+            nexts,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<Nexts, T> PushSurfaceReversed for TeeNPushSurfaceReversed<Nexts, T>
+where
+    Nexts: PushSurfaceReversedList<T>,
+    T: Clone,
+{
+    type ItemIn = T;
+
+    type OutputHandoffs = Nexts::OutputHandoffs;
+    type Build = TeeNPushBuild<Nexts::Build, T>;
+
+    fn into_parts(self) -> (Self::OutputHandoffs, Self::Build) {
+        let (connect, build) = self.nexts.into_parts();
+        (connect, TeeNPushBuild::new(build))
+    }
+}
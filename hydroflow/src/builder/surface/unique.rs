@@ -0,0 +1,99 @@
+use super::{BaseSurface, PullSurface, PushSurface, PushSurfaceReversed};
+
+use std::hash::Hash;
+
+use crate::builder::build::pull_unique::UniquePullBuild;
+use crate::builder::build::push_unique::UniquePushBuild;
+
+pub struct UniqueSurface<Prev>
+where
+    Prev: BaseSurface,
+{
+    prev: Prev,
+    per_tick: bool,
+}
+impl<Prev> UniqueSurface<Prev>
+where
+    Prev: BaseSurface,
+    Prev::ItemOut: Eq + Hash + Clone,
+{
+    pub fn new(prev: Prev, per_tick: bool) -> Self {
+        Self { prev, per_tick }
+    }
+}
+
+impl<Prev> BaseSurface for UniqueSurface<Prev>
+where
+    Prev: BaseSurface,
+    Prev::ItemOut: Eq + Hash + Clone,
+{
+    type ItemOut = Prev::ItemOut;
+}
+
+impl<Prev> PullSurface for UniqueSurface<Prev>
+where
+    Prev: PullSurface,
+    Prev::ItemOut: Eq + Hash + Clone,
+{
+    type InputHandoffs = Prev::InputHandoffs;
+    type Build = UniquePullBuild<Prev::Build>;
+
+    fn into_parts(self) -> (Self::InputHandoffs, Self::Build) {
+        let (connect, build) = self.prev.into_parts();
+        let build = UniquePullBuild::new(build, self.per_tick);
+        (connect, build)
+    }
+}
+
+impl<Prev> PushSurface for UniqueSurface<Prev>
+where
+    Prev: PushSurface,
+    Prev::ItemOut: Eq + Hash + Clone,
+{
+    type Output<Next>
+    where
+        Next: PushSurfaceReversed<ItemIn = Self::ItemOut>,
+    = Prev::Output<UniquePushSurfaceReversed<Next>>;
+
+    fn push_to<Next>(self, next: Next) -> Self::Output<Next>
+    where
+        Next: PushSurfaceReversed<ItemIn = Self::ItemOut>,
+    {
+        self.prev
+            .push_to(UniquePushSurfaceReversed::new(next, self.per_tick))
+    }
+}
+
+pub struct UniquePushSurfaceReversed<Next>
+where
+    Next: PushSurfaceReversed,
+{
+    next: Next,
+    per_tick: bool,
+}
+impl<Next> UniquePushSurfaceReversed<Next>
+where
+    Next: PushSurfaceReversed,
+    Next::ItemIn: Eq + Hash + Clone,
+{
+    pub fn new(next: Next, per_tick: bool) -> Self {
+        Self { next, per_tick }
+    }
+}
+
+impl<Next> PushSurfaceReversed for UniquePushSurfaceReversed<Next>
+where
+    Next: PushSurfaceReversed,
+    Next::ItemIn: Eq + Hash + Clone,
+{
+    type ItemIn = Next::ItemIn;
+
+    type OutputHandoffs = Next::OutputHandoffs;
+    type Build = UniquePushBuild<Next::Build>;
+
+    fn into_parts(self) -> (Self::OutputHandoffs, Self::Build) {
+        let (connect, build) = self.next.into_parts();
+        let build = UniquePushBuild::new(build, self.per_tick);
+        (connect, build)
+    }
+}
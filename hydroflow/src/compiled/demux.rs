@@ -0,0 +1,39 @@
+use super::{Pusherator, PusheratorList};
+
+use std::marker::PhantomData;
+
+/// Routes each item to the pusherator at the index returned by `f`. Items with
+/// an out-of-range index are dropped.
+pub struct Demux<T, F, Outs>
+where
+    F: Fn(&T) -> usize,
+    Outs: PusheratorList<T>,
+{
+    outs: Outs,
+    f: F,
+    _marker: PhantomData<T>,
+}
+impl<T, F, Outs> Pusherator for Demux<T, F, Outs>
+where
+    F: Fn(&T) -> usize,
+    Outs: PusheratorList<T>,
+{
+    type Item = T;
+    fn give(&mut self, item: Self::Item) {
+        let index = (self.f)(&item);
+        self.outs.give_at(index, item);
+    }
+}
+impl<T, F, Outs> Demux<T, F, Outs>
+where
+    F: Fn(&T) -> usize,
+    Outs: PusheratorList<T>,
+{
+    pub fn new(f: F, outs: Outs) -> Self {
+        Self {
+            outs,
+            f,
+            _marker: PhantomData,
+        }
+    }
+}
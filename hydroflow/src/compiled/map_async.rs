@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::task::{Context, Poll, Waker};
+
+use futures::stream::{FuturesOrdered, FuturesUnordered, StreamExt};
+
+enum InFlight<Fut>
+where
+    Fut: Future,
+{
+    Ordered(FuturesOrdered<Fut>),
+    Unordered(FuturesUnordered<Fut>),
+}
+
+/// Futures started by [`MapAsync`] which have not finished yet, along with
+/// queued inputs waiting for a free slot under the concurrency `limit`.
+pub struct MapAsyncState<In, Fut>
+where
+    Fut: Future,
+{
+    queued: VecDeque<In>,
+    in_flight: InFlight<Fut>,
+    limit: usize,
+}
+
+impl<In, Fut> MapAsyncState<In, Fut>
+where
+    Fut: Future,
+{
+    /// Creates a new state allowing up to `limit` futures in flight at once.
+    /// If `ordered` is set, results are emitted in input order.
+    pub fn new(limit: usize, ordered: bool) -> Self {
+        assert!(0 < limit, "map_async concurrency limit must be nonzero.");
+        let in_flight = if ordered {
+            InFlight::Ordered(FuturesOrdered::new())
+        } else {
+            InFlight::Unordered(FuturesUnordered::new())
+        };
+        Self {
+            queued: VecDeque::new(),
+            in_flight,
+            limit,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match &self.in_flight {
+            InFlight::Ordered(futs) => futs.len(),
+            InFlight::Unordered(futs) => futs.len(),
+        }
+    }
+
+    fn push(&mut self, fut: Fut) {
+        match &mut self.in_flight {
+            InFlight::Ordered(futs) => futs.push(fut),
+            InFlight::Unordered(futs) => futs.push(fut),
+        }
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Fut::Output>> {
+        match &mut self.in_flight {
+            InFlight::Ordered(futs) => futs.poll_next_unpin(cx),
+            InFlight::Unordered(futs) => futs.poll_next_unpin(cx),
+        }
+    }
+}
+
+/// Maps each item to a future and yields the futures' outputs as they become
+/// ready.
+///
+/// Futures are polled with `waker`, so the subgraph is rescheduled when one of
+/// them makes progress. Items and futures which are not ready by the end of
+/// the run are kept in the state for the next run.
+pub struct MapAsync<'a, I, F, Fut>
+where
+    I: Iterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future,
+{
+    prev: I,
+    func: &'a mut F,
+    state: &'a mut MapAsyncState<I::Item, Fut>,
+    waker: Waker,
+}
+
+impl<'a, I, F, Fut> Iterator for MapAsync<'a, I, F, Fut>
+where
+    I: Iterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future,
+{
+    type Item = Fut::Output;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.state.len() < self.state.limit {
+            match self.state.queued.pop_front().or_else(|| self.prev.next()) {
+                Some(item) => {
+                    let fut = (self.func)(item);
+                    self.state.push(fut);
+                }
+                None => break,
+            }
+        }
+
+        let mut cx = Context::from_waker(&self.waker);
+        match self.state.poll_next(&mut cx) {
+            Poll::Ready(Some(out)) => Some(out),
+            Poll::Ready(None) | Poll::Pending => {
+                // Hold onto any remaining input until there is room for it.
+                self.state.queued.extend(&mut self.prev);
+                None
+            }
+        }
+    }
+}
+
+impl<'a, I, F, Fut> MapAsync<'a, I, F, Fut>
+where
+    I: Iterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future,
+{
+    pub fn new(
+        prev: I,
+        func: &'a mut F,
+        state: &'a mut MapAsyncState<I::Item, Fut>,
+        waker: Waker,
+    ) -> Self {
+        Self {
+            prev,
+            func,
+            state,
+            waker,
+        }
+    }
+}
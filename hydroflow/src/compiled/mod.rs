@@ -1,22 +1,82 @@
+pub mod demux;
 pub mod filter;
 pub mod filter_map;
 pub mod flatten;
 pub mod for_each;
 pub mod group_by;
 pub mod map;
+pub mod map_async;
 pub mod partition;
 pub mod pivot;
 pub mod pull;
 pub mod push_handoff;
 pub mod tee;
+pub mod unique;
 
 use std::marker::PhantomData;
 
+use crate::scheduled::type_list::TypeList;
+
 pub trait Pusherator: Sized {
     type Item;
     fn give(&mut self, item: Self::Item);
 }
 
+/// A variadic list of [`Pusherator`]s which all accept the same `T`, used by
+/// N-ary operators like [`tee::TeeN`] and [`demux::Demux`].
+pub trait PusheratorList<T>: TypeList {
+    const LEN: usize;
+
+    /// Gives `item` to every pusherator in the list, cloning it for all but
+    /// the last.
+    fn give_all(&mut self, item: T)
+    where
+        T: Clone;
+
+    /// Gives `item` to the pusherator at `index`, dropping it if `index` is
+    /// out of range.
+    fn give_at(&mut self, index: usize, item: T);
+}
+impl<T, X, Rest> PusheratorList<T> for (X, Rest)
+where
+    X: Pusherator<Item = T>,
+    Rest: PusheratorList<T>,
+{
+    const LEN: usize = 1 + Rest::LEN;
+
+    fn give_all(&mut self, item: T)
+    where
+        T: Clone,
+    {
+        let (x, rest) = self;
+        if 0 == Rest::LEN {
+            x.give(item);
+        } else {
+            x.give(item.clone());
+            rest.give_all(item);
+        }
+    }
+
+    fn give_at(&mut self, index: usize, item: T) {
+        let (x, rest) = self;
+        match index {
+            0 => x.give(item),
+            _ => rest.give_at(index - 1, item),
+        }
+    }
+}
+impl<T> PusheratorList<T> for () {
+    const LEN: usize = 0;
+
+    fn give_all(&mut self, _item: T)
+    where
+        T: Clone,
+    {
+    }
+
+    fn give_at(&mut self, _index: usize, _item: T) {}
+}
+
 pub trait IteratorToPusherator: Iterator {
     fn pull_to_push(self) -> pivot::PivotBuild<Self>
     where
@@ -108,10 +168,18 @@ mod tests {
     use std::rc::Rc;
 
     use super::{
-        filter::Filter, for_each::ForEach, group_by::GroupBy, map::Map, partition::Partition,
-        pivot::Pivot, tee::Tee, Pusherator,
+        demux::Demux,
+        filter::Filter,
+        for_each::ForEach,
+        group_by::GroupBy,
+        map::Map,
+        partition::Partition,
+        pivot::Pivot,
+        tee::{Tee, TeeN},
+        Pusherator,
     };
     use crate::lang::lattice::ord::MaxRepr;
+    use crate::tl;
 
     #[test]
     fn linear_chains() {
@@ -163,6 +231,49 @@ mod tests {
         assert_eq!(right, vec![0, 1, 2, 3, 4]);
     }
 
+    #[test]
+    fn tee_n() {
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        let mut c = Vec::new();
+        let mut pusher = TeeN::new(tl!(
+            ForEach::new(|x| a.push(x)),
+            ForEach::new(|x| b.push(x)),
+            ForEach::new(|x| c.push(x)),
+        ));
+
+        for i in 0..3 {
+            pusher.give(i);
+        }
+
+        assert_eq!(a, vec![0, 1, 2]);
+        assert_eq!(b, vec![0, 1, 2]);
+        assert_eq!(c, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn demux() {
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        let mut c = Vec::new();
+        let mut pusher = Demux::new(
+            |x: &usize| x % 4,
+            tl!(
+                ForEach::new(|x| a.push(x)),
+                ForEach::new(|x| b.push(x)),
+                ForEach::new(|x| c.push(x)),
+            ),
+        );
+
+        for i in 0..8 {
+            pusher.give(i);
+        }
+
+        assert_eq!(a, vec![0, 4]);
+        assert_eq!(b, vec![1, 5]);
+        assert_eq!(c, vec![2, 6]);
+    }
+
     #[test]
     fn tee_rcs() {
         let mut left = Vec::new();
@@ -1,6 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
 use std::ops::Range;
 
+use crate::lang::lattice::{Convert, LatticeRepr, Merge};
+use crate::scheduled::type_list::TypeList;
+
 #[derive(Debug)]
 pub struct BatchJoinState<K, BufV> {
     tab: HashMap<K, Vec<BufV>>,
@@ -253,9 +257,272 @@ where
     }
 }
 
+pub struct LatticeJoinState<K, L1, L2>
+where
+    L1: LatticeRepr,
+    L2: LatticeRepr,
+{
+    ltab: HashMap<K, L1::Repr>,
+    rtab: HashMap<K, L2::Repr>,
+}
+
+impl<K, L1, L2> Default for LatticeJoinState<K, L1, L2>
+where
+    L1: LatticeRepr,
+    L2: LatticeRepr,
+{
+    fn default() -> Self {
+        Self {
+            ltab: HashMap::new(),
+            rtab: HashMap::new(),
+        }
+    }
+}
+
+/// Joins two keyed streams of lattice deltas. Each side's `D` deltas are
+/// merged into a per-key `L` value, and whenever either side's value for a key
+/// grows, the current values of both sides are emitted if both are present.
+pub struct LatticeJoin<'a, K, I1, L1, D1, I2, L2, D2>
+where
+    K: Eq + std::hash::Hash + Clone,
+    L1: LatticeRepr<Lattice = D1::Lattice> + Merge<D1>,
+    D1: LatticeRepr + Convert<L1>,
+    L2: LatticeRepr<Lattice = D2::Lattice> + Merge<D2>,
+    D2: LatticeRepr + Convert<L2>,
+    I1: Iterator<Item = (K, D1::Repr)>,
+    I2: Iterator<Item = (K, D2::Repr)>,
+{
+    lhs: I1,
+    rhs: I2,
+    state: &'a mut LatticeJoinState<K, L1, L2>,
+    _phantom: PhantomData<fn(D1, D2)>,
+}
+
+impl<'a, K, I1, L1, D1, I2, L2, D2> Iterator for LatticeJoin<'a, K, I1, L1, D1, I2, L2, D2>
+where
+    K: Eq + std::hash::Hash + Clone,
+    L1: LatticeRepr<Lattice = D1::Lattice> + Merge<D1>,
+    D1: LatticeRepr + Convert<L1>,
+    L2: LatticeRepr<Lattice = D2::Lattice> + Merge<D2>,
+    D2: LatticeRepr + Convert<L2>,
+    I1: Iterator<Item = (K, D1::Repr)>,
+    I2: Iterator<Item = (K, D2::Repr)>,
+{
+    type Item = (K, L1::Repr, L2::Repr);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((k, delta)) = self.lhs.next() {
+                if merge_entry::<K, L1, D1>(&mut self.state.ltab, &k, delta) {
+                    if let Some(r) = self.state.rtab.get(&k) {
+                        let l = self.state.ltab.get(&k).unwrap();
+                        return Some((k, l.clone(), r.clone()));
+                    }
+                }
+                continue;
+            }
+
+            if let Some((k, delta)) = self.rhs.next() {
+                if merge_entry::<K, L2, D2>(&mut self.state.rtab, &k, delta) {
+                    if let Some(l) = self.state.ltab.get(&k) {
+                        let r = self.state.rtab.get(&k).unwrap();
+                        return Some((k, l.clone(), r.clone()));
+                    }
+                }
+                continue;
+            }
+            return None;
+        }
+    }
+}
+impl<'a, K, I1, L1, D1, I2, L2, D2> LatticeJoin<'a, K, I1, L1, D1, I2, L2, D2>
+where
+    K: Eq + std::hash::Hash + Clone,
+    L1: LatticeRepr<Lattice = D1::Lattice> + Merge<D1>,
+    D1: LatticeRepr + Convert<L1>,
+    L2: LatticeRepr<Lattice = D2::Lattice> + Merge<D2>,
+    D2: LatticeRepr + Convert<L2>,
+    I1: Iterator<Item = (K, D1::Repr)>,
+    I2: Iterator<Item = (K, D2::Repr)>,
+{
+    pub fn new(lhs: I1, rhs: I2, state: &'a mut LatticeJoinState<K, L1, L2>) -> Self {
+        Self {
+            lhs,
+            rhs,
+            state,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Merges `delta` into the value for `k`, returning `true` if it changed.
+fn merge_entry<K, L, D>(tab: &mut HashMap<K, L::Repr>, k: &K, delta: D::Repr) -> bool
+where
+    K: Eq + std::hash::Hash + Clone,
+    L: LatticeRepr<Lattice = D::Lattice> + Merge<D>,
+    D: LatticeRepr + Convert<L>,
+{
+    match tab.get_mut(k) {
+        Some(val) => L::merge(val, delta),
+        None => {
+            tab.insert(k.clone(), D::convert(delta));
+            true
+        }
+    }
+}
+
+/// A variadic list of [`Iterator`]s which all yield the same `T`.
+pub trait IteratorList<T>: TypeList {
+    const LEN: usize;
+
+    /// Calls `next()` on the iterator at `index`, returning `None` if `index`
+    /// is out of range.
+    fn next_at(&mut self, index: usize) -> Option<T>;
+}
+impl<T, X, Rest> IteratorList<T> for (X, Rest)
+where
+    X: Iterator<Item = T>,
+    Rest: IteratorList<T>,
+{
+    const LEN: usize = 1 + Rest::LEN;
+
+    fn next_at(&mut self, index: usize) -> Option<T> {
+        let (x, rest) = self;
+        match index {
+            0 => x.next(),
+            _ => rest.next_at(index - 1),
+        }
+    }
+}
+impl<T> IteratorList<T> for () {
+    const LEN: usize = 0;
+
+    fn next_at(&mut self, _index: usize) -> Option<T> {
+        None
+    }
+}
+
+/// Interleaves the iterators of an [`IteratorList`], taking one item from each
+/// in turn and skipping those which are exhausted. An iterator is never polled
+/// again once it has returned `None`, so they need not be fused.
+pub struct RoundRobin<T, Iters>
+where
+    Iters: IteratorList<T>,
+{
+    iters: Iters,
+    cursor: usize,
+    /// Which iterators have returned `None`.
+    exhausted: Vec<bool>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, Iters> Iterator for RoundRobin<T, Iters>
+where
+    Iters: IteratorList<T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for _ in 0..Iters::LEN {
+            let index = self.cursor;
+            self.cursor = (self.cursor + 1) % Iters::LEN;
+            if self.exhausted[index] {
+                continue;
+            }
+            match self.iters.next_at(index) {
+                Some(item) => return Some(item),
+                None => self.exhausted[index] = true,
+            }
+        }
+        None
+    }
+}
+
+impl<T, Iters> RoundRobin<T, Iters>
+where
+    Iters: IteratorList<T>,
+{
+    pub fn new(iters: Iters) -> Self {
+        Self {
+            iters,
+            cursor: 0,
+            exhausted: vec![false; Iters::LEN],
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ZipState<V1, V2> {
+    lbuf: VecDeque<V1>,
+    rbuf: VecDeque<V2>,
+}
+
+impl<V1, V2> Default for ZipState<V1, V2> {
+    fn default() -> Self {
+        Self {
+            lbuf: VecDeque::new(),
+            rbuf: VecDeque::new(),
+        }
+    }
+}
+
+/// Pairs items from two iterators positionally. Once one side runs out, the
+/// rest of the other side is buffered in the state to be paired in a later
+/// run.
+pub struct Zip<'a, I1, V1, I2, V2>
+where
+    I1: Iterator<Item = V1>,
+    I2: Iterator<Item = V2>,
+{
+    lhs: I1,
+    rhs: I2,
+    state: &'a mut ZipState<V1, V2>,
+}
+
+impl<'a, I1, V1, I2, V2> Iterator for Zip<'a, I1, V1, I2, V2>
+where
+    I1: Iterator<Item = V1>,
+    I2: Iterator<Item = V2>,
+{
+    type Item = (V1, V2);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let l = self.state.lbuf.pop_front().or_else(|| self.lhs.next());
+        let r = self.state.rbuf.pop_front().or_else(|| self.rhs.next());
+        match (l, r) {
+            (Some(l), Some(r)) => Some((l, r)),
+            (Some(l), None) => {
+                self.state.lbuf.push_front(l);
+                self.state.lbuf.extend(&mut self.lhs);
+                None
+            }
+            (None, Some(r)) => {
+                self.state.rbuf.push_front(r);
+                self.state.rbuf.extend(&mut self.rhs);
+                None
+            }
+            (None, None) => None,
+        }
+    }
+}
+impl<'a, I1, V1, I2, V2> Zip<'a, I1, V1, I2, V2>
+where
+    I1: Iterator<Item = V1>,
+    I2: Iterator<Item = V2>,
+{
+    pub fn new(lhs: I1, rhs: I2, state: &'a mut ZipState<V1, V2>) -> Self {
+        Self { lhs, rhs, state }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::compiled::pull::{CrossJoin, CrossJoinState, JoinState, SymmetricHashJoin};
+    use crate::compiled::pull::{
+        CrossJoin, CrossJoinState, JoinState, LatticeJoin, LatticeJoinState, RoundRobin,
+        SymmetricHashJoin, Zip, ZipState,
+    };
+    use crate::lang::lattice::ord::MaxRepr;
 
     #[test]
     fn hash_join() {
@@ -304,4 +571,51 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn round_robin() {
+        // Not fused: yields `None` then resumes.
+        let mut calls = 0;
+        let flaky = std::iter::from_fn(move || {
+            calls += 1;
+            (calls != 2).then(|| calls * 100)
+        });
+        let iters = (vec![1, 2, 3].into_iter(), (flaky, ()));
+
+        assert_eq!(
+            RoundRobin::new(iters).collect::<Vec<_>>(),
+            vec![1, 100, 2, 3]
+        );
+    }
+
+    #[test]
+    fn zip() {
+        let mut state = ZipState::default();
+
+        let zip = Zip::new(0..3, "abcde".chars(), &mut state);
+        assert_eq!(vec![(0, 'a'), (1, 'b'), (2, 'c')], zip.collect::<Vec<_>>());
+
+        let zip = Zip::new(3..7, std::iter::empty(), &mut state);
+        assert_eq!(vec![(3, 'd'), (4, 'e')], zip.collect::<Vec<_>>());
+
+        let zip = Zip::new(std::iter::empty(), "f".chars(), &mut state);
+        assert_eq!(vec![(5, 'f')], zip.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn lattice_join() {
+        let lhs = vec![("a", 1), ("b", 5), ("a", 3), ("a", 2)].into_iter();
+        let rhs = vec![("a", 10), ("c", 1), ("a", 4)].into_iter();
+
+        let mut state = LatticeJoinState::<_, MaxRepr<u64>, MaxRepr<u64>>::default();
+        let join =
+            LatticeJoin::<_, _, _, MaxRepr<u64>, _, _, MaxRepr<u64>>::new(lhs, rhs, &mut state);
+        assert_eq!(vec![("a", 3, 10)], join.collect::<Vec<_>>());
+
+        let lhs = vec![("c", 7)].into_iter();
+        let rhs = vec![("a", 20)].into_iter();
+        let join =
+            LatticeJoin::<_, _, _, MaxRepr<u64>, _, _, MaxRepr<u64>>::new(lhs, rhs, &mut state);
+        assert_eq!(vec![("c", 7, 1), ("a", 3, 20)], join.collect::<Vec<_>>());
+    }
 }
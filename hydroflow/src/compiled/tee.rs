@@ -1,4 +1,4 @@
-use super::{Pusherator, PusheratorBuild};
+use super::{Pusherator, PusheratorBuild, PusheratorList};
 
 use std::marker::PhantomData;
 
@@ -79,3 +79,35 @@ where
         self.prev.build(Tee::new(self.first_out, input))
     }
 }
+
+/// Gives a copy of each item to every pusherator in a [`PusheratorList`].
+pub struct TeeN<T, Outs>
+where
+    T: Clone,
+    Outs: PusheratorList<T>,
+{
+    outs: Outs,
+    _marker: PhantomData<T>,
+}
+impl<T, Outs> Pusherator for TeeN<T, Outs>
+where
+    T: Clone,
+    Outs: PusheratorList<T>,
+{
+    type Item = T;
+    fn give(&mut self, item: Self::Item) {
+        self.outs.give_all(item);
+    }
+}
+impl<T, Outs> TeeN<T, Outs>
+where
+    T: Clone,
+    Outs: PusheratorList<T>,
+{
+    pub fn new(outs: Outs) -> Self {
+        Self {
+            outs,
+            _marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// Tracks which items have already been seen, for removing duplicates.
+///
+/// If `per_tick` is set the seen items are forgotten at the start of each
+/// new tick, otherwise they are remembered forever.
+#[derive(Debug)]
+pub struct UniqueState<T> {
+    seen: HashSet<T>,
+    per_tick: bool,
+    tick: usize,
+}
+
+impl<T> UniqueState<T> {
+    pub fn new(per_tick: bool) -> Self {
+        Self {
+            seen: HashSet::new(),
+            per_tick,
+            tick: 0,
+        }
+    }
+
+    /// Must be called at the start of each subgraph run with the current tick.
+    pub fn start_run(&mut self, tick: usize) {
+        if self.per_tick && self.tick != tick {
+            self.seen.clear();
+        }
+        self.tick = tick;
+    }
+}
+
+impl<T> UniqueState<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// Returns `true` if `item` has not been seen yet, and records it as seen.
+    pub fn insert(&mut self, item: &T) -> bool {
+        if self.seen.contains(item) {
+            false
+        } else {
+            self.seen.insert(item.clone());
+            true
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! Event time: items carrying the time they happened, interleaved with
+//! watermarks promising that no earlier items will follow.
+//!
+//! Event-time streams flow through ordinary handoffs as [`Event`]s, so
+//! watermarks travel in-band with the items they bound. Watermarks only move
+//! forward: they are merged with the [`Max`](super::lattice::ord::Max)
+//! lattice, so a stale watermark arriving after a newer one has no effect.
+//!
+//! Stateful operators like [`TumblingWindows`] and [`WindowJoin`] hold items
+//! in per-window state until the watermark passes the end of their window,
+//! then emit the window's result and drop its state. Items earlier than the
+//! watermark are late, and are dropped and counted rather than reopening a
+//! closed window.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+use serde::{Deserialize, Serialize};
+
+use super::lattice::{ord::MaxRepr, Merge};
+
+pub type Timestamp = u64;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Event<T> {
+    /// An item which happened at the given time.
+    Item(Timestamp, T),
+    /// No more items earlier than the given time will follow.
+    Watermark(Timestamp),
+}
+
+impl<T> Event<T> {
+    /// Maps the item, keeping its time. Watermarks are passed through.
+    pub fn map<U, F>(self, f: F) -> Event<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        match self {
+            Event::Item(time, item) => Event::Item(time, f(item)),
+            Event::Watermark(time) => Event::Watermark(time),
+        }
+    }
+
+    pub fn time(&self) -> Timestamp {
+        match self {
+            Event::Item(time, _) | Event::Watermark(time) => *time,
+        }
+    }
+}
+
+/// Advances `watermark` to `time`, returning whether it moved.
+fn advance(watermark: &mut Timestamp, time: Timestamp) -> bool {
+    <MaxRepr<Timestamp> as Merge<MaxRepr<Timestamp>>>::merge(watermark, time)
+}
+
+/// Start of the window of `size` containing `time`.
+fn window_start(time: Timestamp, size: Timestamp) -> Timestamp {
+    time - time % size
+}
+
+/// Splits off the windows which have closed once the watermark reaches
+/// `watermark`, returning them along with the start of the earliest window
+/// which may still be open. A watermark of [`Timestamp::MAX`] closes every
+/// window.
+fn close_windows<W>(
+    windows: &mut BTreeMap<Timestamp, W>,
+    watermark: Timestamp,
+    size: Timestamp,
+) -> (BTreeMap<Timestamp, W>, Timestamp) {
+    if watermark == Timestamp::MAX {
+        return (std::mem::take(windows), Timestamp::MAX);
+    }
+    let cutoff = window_start(watermark, size);
+    let open = windows.split_off(&cutoff);
+    (std::mem::replace(windows, open), cutoff)
+}
+
+/// Attaches event times to items, along with watermarks assuming items
+/// arrive at most `max_delay` behind the latest one seen.
+pub struct BoundedDelay<F> {
+    time_of: F,
+    max_delay: Timestamp,
+    watermark: Timestamp,
+}
+
+impl<F> BoundedDelay<F> {
+    /// `time_of` gives the event time of each item.
+    pub fn new(time_of: F, max_delay: Timestamp) -> Self {
+        Self {
+            time_of,
+            max_delay,
+            watermark: 0,
+        }
+    }
+
+    /// Returns `item` as an [`Event`], followed by a new watermark if it
+    /// moved the watermark forward.
+    pub fn attach<T>(&mut self, item: T) -> impl Iterator<Item = Event<T>>
+    where
+        F: FnMut(&T) -> Timestamp,
+    {
+        let time = (self.time_of)(&item);
+        let watermark = time.saturating_sub(self.max_delay);
+        let watermark =
+            advance(&mut self.watermark, watermark).then(|| Event::Watermark(watermark));
+        std::iter::once(Event::Item(time, item)).chain(watermark)
+    }
+}
+
+/// Groups items into consecutive non-overlapping windows of `size`, the
+/// window starting at `start` holding items with times in `start..start +
+/// size`. Each window is emitted once closed, as an item at its start time,
+/// followed by a watermark at the start of the earliest window still open.
+pub struct TumblingWindows<T> {
+    size: Timestamp,
+    watermark: Timestamp,
+    output_watermark: Timestamp,
+    /// The items of each open window, by start time.
+    windows: BTreeMap<Timestamp, Vec<T>>,
+    late: u64,
+}
+
+impl<T> TumblingWindows<T> {
+    pub fn new(size: Timestamp) -> Self {
+        assert!(0 < size, "Window size must be nonzero.");
+        Self {
+            size,
+            watermark: 0,
+            output_watermark: 0,
+            windows: BTreeMap::new(),
+            late: 0,
+        }
+    }
+
+    /// The number of late items dropped so far.
+    pub fn late(&self) -> u64 {
+        self.late
+    }
+
+    /// Handles `event`, returning any windows it closed.
+    pub fn push(&mut self, event: Event<T>) -> Vec<Event<Vec<T>>> {
+        match event {
+            Event::Item(time, item) => {
+                if time < self.watermark {
+                    self.late += 1;
+                } else {
+                    let start = window_start(time, self.size);
+                    self.windows.entry(start).or_default().push(item);
+                }
+                Vec::new()
+            }
+            Event::Watermark(time) => {
+                if !advance(&mut self.watermark, time) {
+                    return Vec::new();
+                }
+                let (closed, cutoff) = close_windows(&mut self.windows, self.watermark, self.size);
+                let mut out: Vec<_> = closed
+                    .into_iter()
+                    .map(|(start, items)| Event::Item(start, items))
+                    .collect();
+                if advance(&mut self.output_watermark, cutoff) {
+                    out.push(Event::Watermark(cutoff));
+                }
+                out
+            }
+        }
+    }
+}
+
+/// Which input of a [`WindowJoin`] an event came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JoinSide<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// An event from either input of a [`WindowJoin`].
+pub type JoinInput<K, A, B> = JoinSide<Event<(K, A)>, Event<(K, B)>>;
+
+type JoinWindow<K, A, B> = (HashMap<K, Vec<A>>, HashMap<K, Vec<B>>);
+
+/// Joins two keyed event-time streams within tumbling windows of `size`:
+/// `(key, a)` and `(key, b)` match if their times fall in the same window.
+/// The matches for a window are emitted once the watermarks of both inputs
+/// have passed its end, as items at the window's start time.
+pub struct WindowJoin<K, A, B> {
+    size: Timestamp,
+    left_watermark: Timestamp,
+    right_watermark: Timestamp,
+    output_watermark: Timestamp,
+    windows: BTreeMap<Timestamp, JoinWindow<K, A, B>>,
+    late: u64,
+}
+
+impl<K, A, B> WindowJoin<K, A, B>
+where
+    K: Eq + Hash + Clone,
+    A: Clone,
+    B: Clone,
+{
+    pub fn new(size: Timestamp) -> Self {
+        assert!(0 < size, "Window size must be nonzero.");
+        Self {
+            size,
+            left_watermark: 0,
+            right_watermark: 0,
+            output_watermark: 0,
+            windows: BTreeMap::new(),
+            late: 0,
+        }
+    }
+
+    /// The number of late items dropped so far.
+    pub fn late(&self) -> u64 {
+        self.late
+    }
+
+    /// The combined watermark of both inputs.
+    fn watermark(&self) -> Timestamp {
+        self.left_watermark.min(self.right_watermark)
+    }
+
+    /// Handles `event`, returning the matches in any windows it closed.
+    pub fn push(&mut self, event: JoinInput<K, A, B>) -> Vec<Event<(K, A, B)>> {
+        let watermark = self.watermark();
+        let start = |time| window_start(time, self.size);
+        match event {
+            JoinSide::Left(Event::Item(time, (key, a))) => {
+                if time < watermark {
+                    self.late += 1;
+                } else {
+                    let window = self.windows.entry(start(time)).or_default();
+                    window.0.entry(key).or_default().push(a);
+                }
+                Vec::new()
+            }
+            JoinSide::Right(Event::Item(time, (key, b))) => {
+                if time < watermark {
+                    self.late += 1;
+                } else {
+                    let window = self.windows.entry(start(time)).or_default();
+                    window.1.entry(key).or_default().push(b);
+                }
+                Vec::new()
+            }
+            JoinSide::Left(Event::Watermark(time)) => {
+                advance(&mut self.left_watermark, time);
+                self.close()
+            }
+            JoinSide::Right(Event::Watermark(time)) => {
+                advance(&mut self.right_watermark, time);
+                self.close()
+            }
+        }
+    }
+
+    fn close(&mut self) -> Vec<Event<(K, A, B)>> {
+        let watermark = self.watermark();
+        let (closed, cutoff) = close_windows(&mut self.windows, watermark, self.size);
+        let mut out = Vec::new();
+        for (start, (left, right)) in closed {
+            for (key, as_) in left {
+                if let Some(bs) = right.get(&key) {
+                    for a in as_.iter() {
+                        for b in bs.iter() {
+                            out.push(Event::Item(start, (key.clone(), a.clone(), b.clone())));
+                        }
+                    }
+                }
+            }
+        }
+        if advance(&mut self.output_watermark, cutoff) {
+            out.push(Event::Watermark(cutoff));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded_delay() {
+        let mut delay = BoundedDelay::new(|&t: &u64| t, 10);
+        assert_eq!(
+            vec![Event::Item(25, 25), Event::Watermark(15)],
+            delay.attach(25).collect::<Vec<_>>()
+        );
+        // Out of order, so the watermark doesn't move.
+        assert_eq!(
+            vec![Event::Item(20, 20)],
+            delay.attach(20).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![Event::Item(30, 30), Event::Watermark(20)],
+            delay.attach(30).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_tumbling_windows() {
+        let mut windows = TumblingWindows::new(10);
+        assert!(windows.push(Event::Item(3, 'a')).is_empty());
+        assert!(windows.push(Event::Item(12, 'b')).is_empty());
+        assert!(windows.push(Event::Item(7, 'c')).is_empty());
+        // Doesn't reach the end of the first window.
+        assert!(windows.push(Event::Watermark(9)).is_empty());
+        assert_eq!(
+            vec![Event::Item(0, vec!['a', 'c']), Event::Watermark(10)],
+            windows.push(Event::Watermark(15))
+        );
+        // Late, its window has already been emitted.
+        assert!(windows.push(Event::Item(8, 'd')).is_empty());
+        assert_eq!(1, windows.late());
+        // A stale watermark changes nothing.
+        assert!(windows.push(Event::Watermark(11)).is_empty());
+        assert_eq!(
+            vec![Event::Item(10, vec!['b']), Event::Watermark(Timestamp::MAX)],
+            windows.push(Event::Watermark(Timestamp::MAX))
+        );
+    }
+
+    #[test]
+    fn test_window_join() {
+        let mut join = WindowJoin::new(10);
+        assert!(join
+            .push(JoinSide::Left(Event::Item(1, ("k", 'a'))))
+            .is_empty());
+        assert!(join
+            .push(JoinSide::Right(Event::Item(5, ("k", 'x'))))
+            .is_empty());
+        // Different window.
+        assert!(join
+            .push(JoinSide::Right(Event::Item(15, ("k", 'y'))))
+            .is_empty());
+        // Different key.
+        assert!(join
+            .push(JoinSide::Right(Event::Item(6, ("j", 'z'))))
+            .is_empty());
+
+        // Only one side has passed the window.
+        assert!(join.push(JoinSide::Left(Event::Watermark(20))).is_empty());
+        assert_eq!(
+            vec![Event::Item(0, ("k", 'a', 'x')), Event::Watermark(10)],
+            join.push(JoinSide::Right(Event::Watermark(10)))
+        );
+
+        assert!(join
+            .push(JoinSide::Left(Event::Item(2, ("k", 'b'))))
+            .is_empty());
+        assert_eq!(1, join.late());
+    }
+}
@@ -0,0 +1,237 @@
+//! Vector clocks and causally-ordered values.
+//!
+//! A vector clock maps each node to the number of events seen from it, and
+//! is a [`MapUnion`](super::map_union::MapUnion) of [`Max`](super::ord::Max) counters: merging
+//! two clocks takes the latest count from each node, and one clock is less
+//! than another iff the event it stamps happened before the other's. A
+//! [`CausalRepr`] value pairs a clock with a value using [`DomPairRepr`], so
+//! a causally later write replaces an earlier one, while the values of
+//! concurrent writes are merged.
+//!
+//! [`CausalBuffer`] delivers messages stamped with vector clocks in causal
+//! order, holding each one until every message it depends on has been
+//! delivered.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::dom_pair::DomPairRepr;
+use super::map_union::MapUnionRepr;
+use super::ord::MaxRepr;
+use super::{Compare, LatticeRepr};
+use crate::lang::tag;
+
+pub type VectorClock<K> = HashMap<K, u64>;
+pub type VectorClockRepr<K> = MapUnionRepr<tag::HASH_MAP, K, MaxRepr<u64>>;
+/// A single node's entry, as a delta to merge into a [`VectorClockRepr`].
+pub type VectorClockUpdateRepr<K> = MapUnionRepr<tag::SINGLE, K, MaxRepr<u64>>;
+
+/// A value of lattice `Lr` stamped with the vector clock of the write which
+/// produced it.
+pub type CausalRepr<K, Lr> = DomPairRepr<VectorClockRepr<K>, Lr>;
+pub type Causal<K, Lr> = (VectorClock<K>, <Lr as LatticeRepr>::Repr);
+
+/// Counts a new event at `node`, returning its sequence number.
+pub fn tick<K>(clock: &mut VectorClock<K>, node: K) -> u64
+where
+    K: Eq + Hash,
+{
+    let count = clock.entry(node).or_default();
+    *count += 1;
+    *count
+}
+
+/// The causal order between the events stamped `a` and `b`, or `None` if
+/// they are concurrent.
+pub fn causal_order<K>(a: &VectorClock<K>, b: &VectorClock<K>) -> Option<Ordering>
+where
+    K: 'static + Eq + Hash + Clone,
+{
+    <VectorClockRepr<K> as Compare<VectorClockRepr<K>>>::compare(a, b)
+}
+
+/// Whether the event stamped `a` happened before the one stamped `b`.
+pub fn happened_before<K>(a: &VectorClock<K>, b: &VectorClock<K>) -> bool
+where
+    K: 'static + Eq + Hash + Clone,
+{
+    causal_order(a, b) == Some(Ordering::Less)
+}
+
+/// Delivers messages in causal order. Each message is sent by a node along
+/// with the sender's clock after [`tick`]ing it for the send, so it depends
+/// on the sender's previous message and on everything the sender had
+/// delivered before sending it.
+///
+/// Messages which arrive before their dependencies are held until those
+/// have been delivered. Messages which have already been delivered, such as
+/// duplicates, are dropped.
+pub struct CausalBuffer<K, T> {
+    /// The messages delivered so far from each node.
+    delivered: VectorClock<K>,
+    pending: Vec<(K, VectorClock<K>, T)>,
+}
+
+impl<K, T> Default for CausalBuffer<K, T> {
+    fn default() -> Self {
+        Self {
+            delivered: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<K, T> CausalBuffer<K, T>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Starts with `delivered` already delivered, e.g. to include the
+    /// receiver's own messages.
+    pub fn new(delivered: VectorClock<K>) -> Self {
+        Self {
+            delivered,
+            pending: Vec::new(),
+        }
+    }
+
+    /// The messages delivered so far from each node.
+    pub fn delivered(&self) -> &VectorClock<K> {
+        &self.delivered
+    }
+
+    /// The number of messages waiting for their dependencies.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn count(clock: &VectorClock<K>, node: &K) -> u64 {
+        clock.get(node).copied().unwrap_or(0)
+    }
+
+    fn is_deliverable(&self, sender: &K, clock: &VectorClock<K>) -> bool {
+        clock.iter().all(|(node, &count)| {
+            if node == sender {
+                count == Self::count(&self.delivered, node) + 1
+            } else {
+                count <= Self::count(&self.delivered, node)
+            }
+        })
+    }
+
+    /// Receives `msg` from `sender`, stamped with `clock`. Returns the
+    /// messages which can now be delivered, in a causal order.
+    pub fn push(
+        &mut self,
+        sender: K,
+        clock: VectorClock<K>,
+        msg: T,
+    ) -> Vec<(K, VectorClock<K>, T)> {
+        let seq = Self::count(&clock, &sender);
+        if seq <= Self::count(&self.delivered, &sender)
+            || self.pending.iter().any(|(other, other_clock, _)| {
+                *other == sender && Self::count(other_clock, other) == seq
+            })
+        {
+            return Vec::new();
+        }
+        self.pending.push((sender, clock, msg));
+
+        let mut out = Vec::new();
+        while let Some(i) = self
+            .pending
+            .iter()
+            .position(|(sender, clock, _)| self.is_deliverable(sender, clock))
+        {
+            let (sender, clock, msg) = self.pending.swap_remove(i);
+            self.delivered
+                .insert(sender.clone(), Self::count(&clock, &sender));
+            out.push((sender, clock, msg));
+        }
+        out
+    }
+}
+
+fn __assert_merges() {
+    use static_assertions::assert_impl_all;
+
+    use super::Merge;
+
+    assert_impl_all!(VectorClockRepr<usize>: Merge<VectorClockRepr<usize>>);
+    assert_impl_all!(VectorClockRepr<usize>: Merge<VectorClockUpdateRepr<usize>>);
+    assert_impl_all!(CausalRepr<usize, MaxRepr<u64>>: Merge<CausalRepr<usize, MaxRepr<u64>>>);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::lang::lattice::Merge;
+
+    fn clock(entries: &[(&'static str, u64)]) -> VectorClock<&'static str> {
+        entries.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_causal_order() {
+        let a = clock(&[("x", 1)]);
+        let b = clock(&[("x", 1), ("y", 1)]);
+        let c = clock(&[("x", 2)]);
+        assert!(happened_before(&a, &b));
+        assert!(!happened_before(&b, &a));
+        assert_eq!(None, causal_order(&b, &c));
+        assert_eq!(Some(Ordering::Equal), causal_order(&a, &a));
+    }
+
+    #[test]
+    fn test_causal_merge() {
+        type Lr = CausalRepr<&'static str, MaxRepr<u64>>;
+
+        let mut value: Causal<&str, MaxRepr<u64>> = (clock(&[("x", 2)]), 5);
+        // Causally earlier, ignored even though its value is larger.
+        assert!(!<Lr as Merge<Lr>>::merge(
+            &mut value,
+            (clock(&[("x", 1)]), 9)
+        ));
+        assert_eq!((clock(&[("x", 2)]), 5), value);
+        // Causally later, replaces the value even though it is smaller.
+        assert!(<Lr as Merge<Lr>>::merge(
+            &mut value,
+            (clock(&[("x", 2), ("y", 1)]), 3)
+        ));
+        assert_eq!((clock(&[("x", 2), ("y", 1)]), 3), value);
+        // Concurrent, so both the clocks and the values are merged.
+        assert!(<Lr as Merge<Lr>>::merge(
+            &mut value,
+            (clock(&[("x", 3)]), 4)
+        ));
+        assert_eq!((clock(&[("x", 3), ("y", 1)]), 4), value);
+    }
+
+    #[test]
+    fn test_causal_buffer() {
+        let mut buffer = CausalBuffer::default();
+
+        // y's message depends on x's first, which hasn't arrived yet.
+        assert!(buffer
+            .push("y", clock(&[("x", 1), ("y", 1)]), "reply")
+            .is_empty());
+        // x's second message depends on its first.
+        assert!(buffer.push("x", clock(&[("x", 2)]), "second").is_empty());
+        assert_eq!(2, buffer.pending());
+
+        let delivered: Vec<_> = buffer
+            .push("x", clock(&[("x", 1)]), "first")
+            .into_iter()
+            .map(|(_sender, _clock, msg)| msg)
+            .collect();
+        assert_eq!("first", delivered[0]);
+        assert_eq!(3, delivered.len());
+        assert_eq!(0, buffer.pending());
+        assert_eq!(&clock(&[("x", 2), ("y", 1)]), buffer.delivered());
+
+        // Duplicates are dropped.
+        assert!(buffer.push("x", clock(&[("x", 1)]), "first").is_empty());
+        assert_eq!(0, buffer.pending());
+    }
+}
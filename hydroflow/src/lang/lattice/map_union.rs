@@ -124,58 +124,33 @@ where
         this: &<MapUnionRepr<SelfTag, K, SelfLr> as LatticeRepr>::Repr,
         other: &<MapUnionRepr<DeltaTag, K, DeltaLr> as LatticeRepr>::Repr,
     ) -> Option<Ordering> {
-        match this.len().cmp(&other.len()) {
-            Ordering::Greater => {
-                for (key, this_value) in this.entries() {
-                    if let Some(other_value) = other.get(key) {
-                        if let Some(Ordering::Less) = SelfLr::compare(this_value, other_value) {
-                            return None;
-                        }
-                    }
+        let mut current_ordering = Ordering::Equal;
+        let mut shared = 0;
+        for (key, this_value) in this.entries() {
+            let ordering = match other.get(key) {
+                Some(other_value) => {
+                    shared += 1;
+                    SelfLr::compare(this_value, other_value)?
                 }
-                Some(Ordering::Greater)
-            }
-            Ordering::Equal => {
-                let mut current_ordering = Ordering::Equal;
-                for (key, this_value) in this.entries() {
-                    match other.get(key) {
-                        Some(other_value) => {
-                            match SelfLr::compare(this_value, other_value) {
-                                // current_ordering unchanged
-                                Some(Ordering::Equal) => {}
-                                // If we get a strict inequality, check if that conflicts with the current_ordering.
-                                // Then update the current_ordering.
-                                Some(inequal) => {
-                                    if inequal.reverse() == current_ordering {
-                                        // Conflict.
-                                        return None;
-                                    }
-                                    current_ordering = inequal;
-                                }
-                                None => return None,
-                            }
-                        }
-                        None => {
-                            if Ordering::Less == current_ordering {
-                                return None;
-                            }
-                        }
-                    }
+                // A key only `this` has.
+                None => Ordering::Greater,
+            };
+            // Check if a strict inequality conflicts with the current_ordering.
+            if ordering != Ordering::Equal {
+                if ordering.reverse() == current_ordering {
+                    return None;
                 }
-                Some(current_ordering)
+                current_ordering = ordering;
             }
-            Ordering::Less => {
-                // this.len() < other.len()
-                for (key, other_value) in other.entries() {
-                    if let Some(this_value) = this.get(key) {
-                        if let Some(Ordering::Greater) = SelfLr::compare(this_value, other_value) {
-                            return None;
-                        }
-                    }
-                }
-                Some(Ordering::Less)
+        }
+        // Some key only `other` has.
+        if shared < other.len() {
+            if Ordering::Greater == current_ordering {
+                return None;
             }
+            current_ordering = Ordering::Less;
         }
+        Some(current_ordering)
     }
 }
 
@@ -207,3 +182,66 @@ fn __assert_merges() {
     assert_not_impl_any!(OptionMapArraySet: Merge<HashMapHashSet>);
     assert_not_impl_any!(OptionMapArraySet: Merge<HashMapArraySet>);
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::*;
+
+    use crate::lang::lattice::ord::MaxRepr;
+    use crate::lang::lattice::set_union::SetUnionRepr;
+
+    type MaxMap = MapUnionRepr<tag::HASH_MAP, &'static str, MaxRepr<u32>>;
+    type SetMap = MapUnionRepr<tag::HASH_MAP, &'static str, SetUnionRepr<tag::HASH_SET, u32>>;
+
+    fn max_map(entries: &[(&'static str, u32)]) -> HashMap<&'static str, u32> {
+        entries.iter().copied().collect()
+    }
+
+    fn compare(a: &[(&'static str, u32)], b: &[(&'static str, u32)]) -> Option<Ordering> {
+        <MaxMap as Compare<MaxMap>>::compare(&max_map(a), &max_map(b))
+    }
+
+    #[test]
+    fn test_compare_equal() {
+        assert_eq!(Some(Ordering::Equal), compare(&[], &[]));
+        assert_eq!(
+            Some(Ordering::Equal),
+            compare(&[("a", 1), ("b", 2)], &[("a", 1), ("b", 2)])
+        );
+    }
+
+    #[test]
+    fn test_compare_disjoint_keys() {
+        // Same size, but neither contains the other.
+        assert_eq!(None, compare(&[("a", 1)], &[("b", 1)]));
+        assert_eq!(None, compare(&[("a", 1), ("b", 1)], &[("c", 1)]));
+    }
+
+    #[test]
+    fn test_compare_subset() {
+        assert_eq!(
+            Some(Ordering::Less),
+            compare(&[("a", 1)], &[("a", 1), ("b", 1)])
+        );
+        assert_eq!(
+            Some(Ordering::Greater),
+            compare(&[("a", 2), ("b", 1)], &[("a", 1)])
+        );
+        // Fewer keys, but a larger value.
+        assert_eq!(None, compare(&[("a", 2)], &[("a", 1), ("b", 1)]));
+        assert_eq!(None, compare(&[("a", 1), ("b", 1)], &[("a", 2)]));
+    }
+
+    #[test]
+    fn test_compare_incomparable_value() {
+        let a: HashMap<_, HashSet<u32>> = [("a", [1].into()), ("b", [1].into())].into();
+        let b: HashMap<_, HashSet<u32>> = [("a", [1].into()), ("b", [2].into())].into();
+        assert_eq!(None, <SetMap as Compare<SetMap>>::compare(&a, &b));
+        assert_eq!(
+            Some(Ordering::Equal),
+            <SetMap as Compare<SetMap>>::compare(&a, &a)
+        );
+    }
+}
@@ -1,3 +1,5 @@
 pub mod collections;
+pub mod event_time;
 pub mod lattice;
+pub mod partitioner;
 pub mod tag;
@@ -0,0 +1,227 @@
+//! Partitioners assign keys to one of a number of partitions, e.g. to decide
+//! which participant of an exchange a record belongs to.
+//!
+//! Unlike [`std::collections::hash_map::DefaultHasher`], the hash-based
+//! partitioners here are stable: the same key maps to the same partition on
+//! every machine and across runs.
+
+use std::hash::{Hash, Hasher};
+
+/// Assigns keys of type `K` to partitions.
+pub trait Partitioner<K: ?Sized> {
+    /// Returns the partition for `key`, in `0..num_partitions`.
+    /// `num_partitions` must be nonzero.
+    fn partition(&self, key: &K, num_partitions: usize) -> usize;
+}
+
+/// Any `Fn(&K, usize) -> usize` closure can be used as a custom partitioner.
+impl<K: ?Sized, F> Partitioner<K> for F
+where
+    F: Fn(&K, usize) -> usize,
+{
+    fn partition(&self, key: &K, num_partitions: usize) -> usize {
+        (self)(key, num_partitions)
+    }
+}
+
+/// A 64-bit FNV-1a [`Hasher`]. Its output depends only on the bytes written,
+/// so unlike `DefaultHasher` it is consistent across processes and machines.
+/// Integers are written as little-endian bytes, and `usize`/`isize` as 64
+/// bits, so the output doesn't depend on endianness or pointer width.
+#[derive(Debug, Clone, Copy)]
+pub struct Fnv1aHasher(u64);
+
+impl Fnv1aHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+}
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64);
+    }
+}
+
+/// Hashes `key` with [`Fnv1aHasher`].
+pub fn stable_hash<K: Hash + ?Sized>(key: &K) -> u64 {
+    let mut hasher = Fnv1aHasher::default();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Partitions by stable hash modulo the number of partitions. Changing the
+/// number of partitions moves almost every key; see
+/// [`ConsistentHashPartitioner`] for an alternative.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HashPartitioner;
+
+impl<K: Hash + ?Sized> Partitioner<K> for HashPartitioner {
+    fn partition(&self, key: &K, num_partitions: usize) -> usize {
+        (stable_hash(key) % num_partitions as u64) as usize
+    }
+}
+
+/// Partitions by jump consistent hashing (Lamping & Veach, 2014) of the key's
+/// stable hash. Growing from `n` to `n + 1` partitions only moves about
+/// `1 / (n + 1)` of the keys, all of them into the new partition.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ConsistentHashPartitioner;
+
+impl<K: Hash + ?Sized> Partitioner<K> for ConsistentHashPartitioner {
+    fn partition(&self, key: &K, num_partitions: usize) -> usize {
+        jump_consistent_hash(stable_hash(key), num_partitions)
+    }
+}
+
+/// Maps `hash` to a bucket in `0..num_buckets` with jump consistent hashing.
+pub fn jump_consistent_hash(mut hash: u64, num_buckets: usize) -> usize {
+    assert!(0 < num_buckets, "Must have at least one bucket.");
+    let mut b: i64 = -1;
+    let mut j: i64 = 0;
+    while j < num_buckets as i64 {
+        b = j;
+        hash = hash.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+        j = ((b + 1) as f64 * ((1_u64 << 31) as f64 / ((hash >> 33) + 1) as f64)) as i64;
+    }
+    b as usize
+}
+
+/// Picks one of `members` for a key with stable hash `hash`, by rendezvous
+/// (highest random weight) hashing. Unlike [`jump_consistent_hash`] this works
+/// for arbitrary member ids: adding or removing a member only moves the keys
+/// which it gains or loses. Returns `None` if there are no members.
+pub fn rendezvous_hash<I>(hash: u64, members: I) -> Option<u64>
+where
+    I: IntoIterator<Item = u64>,
+{
+    members
+        .into_iter()
+        .max_by_key(|&id| (stable_hash(&(hash, id)), id))
+}
+
+/// Partitions ordered keys by range. Partition `i` holds the keys in
+/// `bounds[i - 1]..bounds[i]`, with the first and last partitions unbounded
+/// below and above respectively. Keys past the last partition (when there are
+/// more bounds than partitions) go to the last partition.
+#[derive(Debug, Clone)]
+pub struct RangePartitioner<K> {
+    bounds: Vec<K>,
+}
+
+impl<K: Ord> RangePartitioner<K> {
+    /// Creates a range partitioner from sorted split points.
+    pub fn new(bounds: Vec<K>) -> Self {
+        assert!(
+            bounds.windows(2).all(|w| w[0] <= w[1]),
+            "Range bounds must be sorted."
+        );
+        Self { bounds }
+    }
+}
+
+impl<K: Ord> Partitioner<K> for RangePartitioner<K> {
+    fn partition(&self, key: &K, num_partitions: usize) -> usize {
+        let idx = self.bounds.partition_point(|bound| bound <= key);
+        idx.min(num_partitions - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_hash() {
+        // Known FNV-1a test vectors.
+        let mut hasher = Fnv1aHasher::default();
+        hasher.write(b"");
+        assert_eq!(0xcbf2_9ce4_8422_2325, hasher.finish());
+        let mut hasher = Fnv1aHasher::default();
+        hasher.write(b"a");
+        assert_eq!(0xaf63_dc4c_8601_ec8c, hasher.finish());
+
+        // Integers hash as little-endian bytes, with `usize` as 64 bits.
+        let mut hasher = Fnv1aHasher::default();
+        hasher.write(&[1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(hasher.finish(), stable_hash(&1_u64));
+        assert_eq!(hasher.finish(), stable_hash(&1_usize));
+        assert_eq!(hasher.finish(), stable_hash(&1_isize));
+    }
+
+    #[test]
+    fn test_consistent_hash_moves_few_keys() {
+        let before: Vec<_> = (0..1000_u64)
+            .map(|k| ConsistentHashPartitioner.partition(&k, 4))
+            .collect();
+        let after: Vec<_> = (0..1000_u64)
+            .map(|k| ConsistentHashPartitioner.partition(&k, 5))
+            .collect();
+        for (&b, &a) in before.iter().zip(after.iter()) {
+            assert!(a == b || a == 4);
+        }
+        let moved = before.iter().zip(after.iter()).filter(|(b, a)| b != a);
+        assert!(moved.count() < 400);
+    }
+
+    #[test]
+    fn test_rendezvous_hash() {
+        assert_eq!(None, rendezvous_hash(5, []));
+        for k in 0..1000_u64 {
+            let hash = stable_hash(&k);
+            let before = rendezvous_hash(hash, [1, 4, 9]).unwrap();
+            let after = rendezvous_hash(hash, [1, 4, 7, 9]).unwrap();
+            assert!(after == before || after == 7);
+            if before != 4 {
+                assert_eq!(Some(before), rendezvous_hash(hash, [1, 9]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_range_partitioner() {
+        let partitioner = RangePartitioner::new(vec![10, 20]);
+        let parts: Vec<_> = [0, 9, 10, 19, 20, 100]
+            .iter()
+            .map(|k| partitioner.partition(k, 3))
+            .collect();
+        assert_eq!(vec![0, 0, 1, 1, 2, 2], parts);
+        assert_eq!(1, partitioner.partition(&100, 2));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{any::Any, sync::mpsc::SyncSender};
+use std::{any::Any, cell::RefCell, sync::mpsc::SyncSender};
 
 use super::{
     graph::{HandoffData, StateData},
@@ -10,11 +10,35 @@ use super::{
 
 pub struct Context<'a> {
     pub(crate) subgraph_id: SubgraphId,
+    pub(crate) current_tick: usize,
+    pub(crate) is_end_of_tick: bool,
     pub(crate) handoffs: &'a mut [HandoffData],
     pub(crate) states: &'a mut [StateData],
+    pub(crate) end_of_tick_queue: &'a RefCell<Vec<SubgraphId>>,
     pub(crate) event_queue_send: &'a mut SyncSender<SubgraphId>,
 }
 impl<'a> Context<'a> {
+    /// Returns the number of the tick this subgraph is running in.
+    pub fn current_tick(&self) -> usize {
+        self.current_tick
+    }
+
+    /// Returns `true` if this run was scheduled by [`Self::schedule_end_of_tick`],
+    /// meaning there was no other work left in the tick when it started.
+    pub fn is_end_of_tick(&self) -> bool {
+        self.is_end_of_tick
+    }
+
+    /// Schedules this subgraph to run again once all other work in the
+    /// current tick is done. Used by blocking operators which need to see
+    /// all of a tick's input before emitting anything.
+    pub fn schedule_end_of_tick(&self) {
+        let mut queue = self.end_of_tick_queue.borrow_mut();
+        if !queue.contains(&self.subgraph_id) {
+            queue.push(self.subgraph_id);
+        }
+    }
+
     pub fn waker(&self) -> std::task::Waker {
         use futures::task::ArcWake;
         use std::sync::Arc;
@@ -25,7 +49,9 @@ impl<'a> Context<'a> {
         }
         impl ArcWake for ContextWaker {
             fn wake_by_ref(arc_self: &Arc<Self>) {
-                arc_self.event_queue_send.send(arc_self.subgraph_id).unwrap(/* TODO(mingwei) */);
+                // The send only fails if the Hydroflow instance was dropped,
+                // in which case there is nothing left to wake.
+                let _ = arc_self.event_queue_send.send(arc_self.subgraph_id);
             }
         }
 
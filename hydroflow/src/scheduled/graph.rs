@@ -1,6 +1,6 @@
 use std::any::Any;
 use std::borrow::Cow;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
 use std::marker::PhantomData;
 use std::sync::mpsc::{self, Receiver, RecvError, SyncSender};
@@ -10,6 +10,7 @@ use ref_cast::RefCast;
 use super::context::Context;
 use super::handoff::handoff_list::PortList;
 use super::handoff::{Handoff, HandoffMeta};
+use super::net::connection_manager::ConnectionManager;
 use super::port::{RecvCtx, RecvPort, SendCtx, SendPort, RECV, SEND};
 use super::reactor::Reactor;
 use super::state::StateHandle;
@@ -23,22 +24,33 @@ pub struct Hydroflow {
 
     states: Vec<StateData>,
 
+    /// Counts calls to [`Self::tick`], used by operators with per-tick state.
+    current_tick: usize,
+    /// Subgraphs to run once all other work in the current tick is done.
+    end_of_tick_queue: RefCell<Vec<SubgraphId>>,
+
     // TODO(mingwei): separate scheduler into its own struct/trait?
     ready_queue: VecDeque<SubgraphId>,
     event_queue_send: SyncSender<SubgraphId>, // TODO(mingwei) remove this, to prevent hanging.
     event_queue_recv: Receiver<SubgraphId>,
+
+    /// Connections shared by this instance's network vertices.
+    connection_manager: ConnectionManager,
 }
 impl Default for Hydroflow {
     fn default() -> Self {
-        let (subgraphs, handoffs, states, ready_queue) = Default::default();
+        let (subgraphs, handoffs, states, end_of_tick_queue, ready_queue) = Default::default();
         let (event_queue_send, event_queue_recv) = mpsc::sync_channel(8_000);
         Self {
             subgraphs,
             handoffs,
             states,
+            current_tick: 0,
+            end_of_tick_queue,
             ready_queue,
             event_queue_send,
             event_queue_recv,
+            connection_manager: Default::default(),
         }
     }
 }
@@ -48,47 +60,81 @@ impl Hydroflow {
         Default::default()
     }
 
+    /// Returns the connection cache shared by this instance's network
+    /// vertices, e.g. to check connection health.
+    pub fn connection_manager(&self) -> &ConnectionManager {
+        &self.connection_manager
+    }
+
     /// Returns a reactor for externally scheduling subgraphs, possibly from another thread.
     pub fn reactor(&self) -> Reactor {
         Reactor::new(self.event_queue_send.clone())
     }
 
+    /// Returns the number of the current (or most recently run) tick.
+    pub fn current_tick(&self) -> usize {
+        self.current_tick
+    }
+
     /// Runs the dataflow until no more work is currently available.
+    ///
+    /// Subgraphs which called [`Context::schedule_end_of_tick`] are run again
+    /// once all other work is done, possibly causing more work.
     pub fn tick(&mut self) {
+        self.current_tick += 1;
+
         // Add any external jobs to ready queue.
         self.try_recv_events();
 
-        while let Some(sg_id) = self.ready_queue.pop_front() {
-            {
-                let sg_data = &mut self.subgraphs[sg_id];
-                // This must be true for the subgraph to be enqueued.
-                assert!(sg_data.is_scheduled.take());
-
-                let context = Context {
-                    subgraph_id: sg_id,
-                    handoffs: &mut self.handoffs,
-                    states: &mut self.states,
-                    event_queue_send: &mut self.event_queue_send,
-                };
-                sg_data.subgraph.run(context);
-            }
+        loop {
+            while let Some(sg_id) = self.ready_queue.pop_front() {
+                {
+                    let sg_data = &mut self.subgraphs[sg_id];
+                    // This must be true for the subgraph to be enqueued.
+                    assert!(sg_data.is_scheduled.take());
+
+                    let context = Context {
+                        subgraph_id: sg_id,
+                        current_tick: self.current_tick,
+                        is_end_of_tick: sg_data.is_end_of_tick.take(),
+                        handoffs: &mut self.handoffs,
+                        states: &mut self.states,
+                        end_of_tick_queue: &self.end_of_tick_queue,
+                        event_queue_send: &mut self.event_queue_send,
+                    };
+                    sg_data.subgraph.run(context);
+                }
 
-            for &handoff_id in self.subgraphs[sg_id].succs.iter() {
-                let handoff = &self.handoffs[handoff_id];
-                if !handoff.handoff.is_bottom() {
-                    for &succ_id in handoff.succs.iter() {
-                        let succ_sg_data = &self.subgraphs[succ_id];
-                        if succ_sg_data.is_scheduled.get() {
-                            // Skip if task is already scheduled.
-                            continue;
+                for &handoff_id in self.subgraphs[sg_id].succs.iter() {
+                    let handoff = &self.handoffs[handoff_id];
+                    if !handoff.handoff.is_bottom() {
+                        for &succ_id in handoff.succs.iter() {
+                            let succ_sg_data = &self.subgraphs[succ_id];
+                            if succ_sg_data.is_scheduled.get() {
+                                // Skip if task is already scheduled.
+                                continue;
+                            }
+                            succ_sg_data.is_scheduled.set(true);
+                            self.ready_queue.push_back(succ_id);
                         }
-                        succ_sg_data.is_scheduled.set(true);
-                        self.ready_queue.push_back(succ_id);
                     }
                 }
+
+                self.try_recv_events();
             }
 
-            self.try_recv_events();
+            // All other work is done, run the subgraphs waiting for the end of the tick.
+            let end_of_tick = self.end_of_tick_queue.take();
+            if end_of_tick.is_empty() {
+                break;
+            }
+            for sg_id in end_of_tick {
+                let sg_data = &self.subgraphs[sg_id];
+                sg_data.is_end_of_tick.set(true);
+                if !sg_data.is_scheduled.replace(true) {
+                    self.ready_queue.push_back(sg_id);
+                }
+            }
         }
     }
 
@@ -347,6 +393,9 @@ struct SubgraphData {
     /// `Self::succs`, as all `SubgraphData` are owned by the same vec
     /// `Hydroflow::subgraphs`.
     is_scheduled: Cell<bool>,
+    /// If this subgraph's next run is at the end of the tick, see
+    /// [`Context::is_end_of_tick`].
+    is_end_of_tick: Cell<bool>,
 }
 impl SubgraphData {
     pub fn new(
@@ -362,6 +411,7 @@ impl SubgraphData {
             preds,
             succs,
             is_scheduled: Cell::new(is_scheduled),
+            is_end_of_tick: Cell::new(false),
         }
     }
 }
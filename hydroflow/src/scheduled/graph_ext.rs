@@ -1,13 +1,15 @@
 use core::task;
 use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fmt::Debug;
 use std::sync::mpsc::SyncSender;
 use std::{pin::Pin, task::Poll};
 
-use futures::Stream;
+use futures::{Sink, Stream};
 
 use super::context::Context;
 use super::graph::Hydroflow;
-use super::handoff::{CanReceive, Handoff};
+use super::handoff::{CanReceive, Handoff, VecHandoff};
 use super::input::Input;
 use super::port::{RecvCtx, RecvPort, SendCtx, SendPort};
 use super::SubgraphId;
@@ -116,6 +118,19 @@ pub trait GraphExt {
         Name: Into<Cow<'static, str>>,
         S: 'static + Stream<Item = T> + Unpin,
         W: 'static + Handoff + CanReceive<T>;
+
+    /// Adds an "output" operator which sends everything from `recv_port` into
+    /// `sink`. Items are buffered while the sink is not ready.
+    fn add_output_to_sink<Name, T, S>(
+        &mut self,
+        name: Name,
+        recv_port: RecvPort<VecHandoff<T>>,
+        sink: S,
+    ) where
+        Name: Into<Cow<'static, str>>,
+        T: 'static,
+        S: 'static + Sink<T> + Unpin,
+        S::Error: Debug;
 }
 
 impl GraphExt for Hydroflow {
@@ -208,4 +223,44 @@ impl GraphExt for Hydroflow {
             }
         });
     }
+
+    fn add_output_to_sink<Name, T, S>(
+        &mut self,
+        name: Name,
+        recv_port: RecvPort<VecHandoff<T>>,
+        sink: S,
+    ) where
+        Name: Into<Cow<'static, str>>,
+        T: 'static,
+        S: 'static + Sink<T> + Unpin,
+        S::Error: Debug,
+    {
+        let mut sink = sink;
+        let mut buffer = VecDeque::new();
+        self.add_subgraph_sink(name, recv_port, move |ctx, recv| {
+            buffer.extend(recv.take_inner());
+
+            // If the sink isn't ready or flushed, the waker will reschedule us.
+            let waker = ctx.waker();
+            let mut cx = task::Context::from_waker(&waker);
+            while !buffer.is_empty() {
+                match Pin::new(&mut sink).poll_ready(&mut cx) {
+                    Poll::Ready(Ok(())) => {
+                        let item = buffer.pop_front().unwrap();
+                        if let Err(e) = Pin::new(&mut sink).start_send(item) {
+                            eprintln!("failed to send to sink: {:?}", e);
+                        }
+                    }
+                    Poll::Ready(Err(e)) => {
+                        eprintln!("sink not ready, dropping {} items: {:?}", buffer.len(), e);
+                        buffer.clear();
+                    }
+                    Poll::Pending => break,
+                }
+            }
+            if let Poll::Ready(Err(e)) = Pin::new(&mut sink).poll_flush(&mut cx) {
+                eprintln!("failed to flush sink: {:?}", e);
+            }
+        });
+    }
 }
@@ -0,0 +1,168 @@
+//! Line-oriented sources and sinks over files and the standard streams.
+//!
+//! Unlike the [network vertices](super::net), these don't need a Tokio
+//! runtime, so they work with [Hydroflow::run] as well as
+//! [Hydroflow::run_async]. File sources produce at most [`BATCH_SIZE`] items
+//! each time they run, rescheduling themselves until the file is exhausted,
+//! so downstream subgraphs process a large file incrementally rather than
+//! all at once.
+
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+
+use crate::scheduled::{
+    graph::Hydroflow,
+    graph_ext::GraphExt,
+    handoff::VecHandoff,
+    port::{RecvPort, SendPort},
+};
+
+pub use csv;
+
+/// Most items a file source produces each time it runs.
+pub const BATCH_SIZE: usize = 1024;
+
+impl Hydroflow {
+    /// Produces each line read from stdin, without the trailing newline.
+    /// Lines are read on a separate thread, which stops at the end of input
+    /// or once this instance is dropped.
+    pub fn add_stdin_lines(&mut self) -> RecvPort<VecHandoff<String>> {
+        let (lines_send, lines_recv) = futures::channel::mpsc::unbounded();
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let line = line.expect("Failed to read stdin as UTF-8.");
+                if lines_send.unbounded_send(line).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let (send_port, recv_port) = self.make_edge("stdin lines handoff");
+        self.add_input_from_stream("stdin lines", send_port, lines_recv.map(Some));
+        recv_port
+    }
+
+    /// Produces each line of the file at `path`, without the trailing
+    /// newline. Stops at the first line which can't be read.
+    pub fn add_file_lines(&mut self, path: &Path) -> std::io::Result<RecvPort<VecHandoff<String>>> {
+        let lines = BufReader::new(File::open(path)?).lines();
+        let name = path.display().to_string();
+        let lines = lines.map_while(move |line| match line {
+            Ok(line) => Some(line),
+            Err(e) => {
+                eprintln!("couldn't read {}: {}", name, e);
+                None
+            }
+        });
+        Ok(self.add_batched_source(format!("{} lines", path.display()), lines))
+    }
+
+    /// Produces each record of the CSV file at `path`, which must have a
+    /// header row. Records which can't be deserialized as `T` are dropped.
+    pub fn add_csv_source<T>(&mut self, path: &Path) -> std::io::Result<RecvPort<VecHandoff<T>>>
+    where
+        T: 'static + DeserializeOwned,
+    {
+        self.add_csv_source_with_reader(&csv::ReaderBuilder::new(), path)
+    }
+
+    /// Like [Self::add_csv_source], but reads the file as configured by
+    /// `reader`, for example with a different delimiter or no header row.
+    pub fn add_csv_source_with_reader<T>(
+        &mut self,
+        reader: &csv::ReaderBuilder,
+        path: &Path,
+    ) -> std::io::Result<RecvPort<VecHandoff<T>>>
+    where
+        T: 'static + DeserializeOwned,
+    {
+        let records = reader
+            .from_reader(File::open(path)?)
+            .into_deserialize()
+            .filter_map(|record| match record {
+                Ok(record) => Some(record),
+                Err(e) => {
+                    eprintln!("couldn't read record: {}", e);
+                    None
+                }
+            });
+        Ok(self.add_batched_source(format!("{} csv", path.display()), records))
+    }
+
+    /// Writes each item to a new file at `path`, one per line, replacing any
+    /// existing file. Output is flushed each time the sink runs.
+    pub fn add_write_lines<T>(&mut self, path: &Path) -> std::io::Result<SendPort<VecHandoff<T>>>
+    where
+        T: 'static + Display,
+    {
+        let file = BufWriter::new(File::create(path)?);
+        Ok(self.add_lines_sink(format!("{} lines", path.display()), file))
+    }
+
+    /// Writes each item to stdout, one per line.
+    pub fn add_stdout_lines<T>(&mut self) -> SendPort<VecHandoff<T>>
+    where
+        T: 'static + Display,
+    {
+        self.add_lines_sink("stdout lines", std::io::stdout())
+    }
+
+    /// Produces the items of `iter`, at most [`BATCH_SIZE`] each run.
+    fn add_batched_source<T, I>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        iter: I,
+    ) -> RecvPort<VecHandoff<T>>
+    where
+        T: 'static,
+        I: 'static + Iterator<Item = T>,
+    {
+        let name = name.into();
+        let mut iter = iter.fuse();
+        let (send_port, recv_port) = self.make_edge(format!("{} handoff", name));
+        self.add_subgraph_source(name, send_port, move |ctx, send| {
+            let mut count = 0;
+            for item in iter.by_ref().take(BATCH_SIZE) {
+                send.give(Some(item));
+                count += 1;
+            }
+            // There may be more, run again once the batch has been processed.
+            if count == BATCH_SIZE {
+                ctx.waker().wake();
+            }
+        });
+        recv_port
+    }
+
+    fn add_lines_sink<T, W>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        mut writer: W,
+    ) -> SendPort<VecHandoff<T>>
+    where
+        T: 'static + Display,
+        W: 'static + Write,
+    {
+        let name = name.into();
+        let (input_port, output_port) =
+            self.make_edge::<_, VecHandoff<T>>(format!("{} handoff", name));
+        let sink_name = name.clone();
+        self.add_subgraph_sink(name, output_port, move |_ctx, recv| {
+            let result = recv
+                .take_inner()
+                .into_iter()
+                .try_for_each(|item| writeln!(writer, "{}", item))
+                .and_then(|()| writer.flush());
+            if let Err(e) = result {
+                eprintln!("couldn't write {}: {}", sink_name, e);
+            }
+        });
+        input_port
+    }
+}
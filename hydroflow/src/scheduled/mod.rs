@@ -3,6 +3,7 @@ pub mod graph;
 pub mod graph_ext;
 pub mod handoff;
 pub mod input;
+pub mod io;
 pub mod net;
 pub mod port;
 pub mod query;
@@ -0,0 +1,290 @@
+//! Batching and compression for the TCP network vertices.
+//!
+//! [Hydroflow::outbound_tcp_vertex_batched] packs the messages for each
+//! destination into a single frame, optionally compressed, and
+//! [Hydroflow::inbound_tcp_vertex_batched] unpacks them again so the
+//! receiving flow sees individual messages. A batch frame looks like:
+//!
+//! ```text
+//! | flags: u8 | body ... |
+//! ```
+//!
+//! where the body is a sequence of `| len: u32 | message ... |` entries, LZ4
+//! compressed (with the uncompressed length prepended) if the low bit of
+//! `flags` is set.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use byteorder::{NetworkEndian, WriteBytesExt};
+use bytes::{Buf, Bytes};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::codec::{BincodeCodec, Decode, Encode};
+use super::network_vertex::{decode_with, Address};
+use crate::scheduled::{
+    graph::Hydroflow,
+    graph_ext::GraphExt,
+    handoff::VecHandoff,
+    port::{RecvPort, SendPort},
+};
+
+const FLAG_LZ4: u8 = 1;
+
+/// Largest decompressed batch accepted, to bound the memory a peer can make
+/// us allocate.
+const MAX_BATCH_LEN: usize = 64 << 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Lz4,
+}
+
+/// When to send the batch of messages waiting for a destination. A batch is
+/// sent as soon as any limit is reached.
+#[derive(Clone, Debug)]
+pub struct BatchConfig {
+    /// Most messages in one batch.
+    pub max_messages: usize,
+    /// Most encoded message bytes in one batch, before compression.
+    pub max_bytes: usize,
+    /// Longest a message waits for its batch to fill up.
+    pub max_delay: Duration,
+    pub compression: Compression,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_messages: 1024,
+            max_bytes: 1 << 20,
+            max_delay: Duration::from_millis(5),
+            compression: Compression::None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Batch {
+    messages: Vec<Bytes>,
+    bytes: usize,
+    /// When the first message was added.
+    started: Option<Instant>,
+}
+
+fn encode_batch(messages: &[Bytes], compression: Compression) -> Bytes {
+    let mut body = Vec::with_capacity(messages.iter().map(|msg| 4 + msg.len()).sum());
+    for msg in messages {
+        body.write_u32::<NetworkEndian>(msg.len() as u32).unwrap();
+        body.extend_from_slice(msg);
+    }
+    match compression {
+        Compression::None => {
+            let mut frame = Vec::with_capacity(1 + body.len());
+            frame.push(0);
+            frame.extend_from_slice(&body);
+            frame.into()
+        }
+        Compression::Lz4 => {
+            let mut frame = vec![FLAG_LZ4];
+            frame.extend_from_slice(&lz4_flex::compress_prepend_size(&body));
+            frame.into()
+        }
+    }
+}
+
+fn decode_batch(mut frame: Bytes) -> Result<Vec<Bytes>, String> {
+    if frame.is_empty() {
+        return Err("empty batch".to_owned());
+    }
+    let flags = frame.get_u8();
+    let mut body = if flags & FLAG_LZ4 != 0 {
+        let (len, _) = lz4_flex::block::uncompressed_size(&frame).map_err(|e| e.to_string())?;
+        if MAX_BATCH_LEN < len {
+            return Err(format!("batch of {} bytes is too large", len));
+        }
+        Bytes::from(lz4_flex::decompress_size_prepended(&frame).map_err(|e| e.to_string())?)
+    } else {
+        frame
+    };
+
+    let mut messages = Vec::new();
+    while body.has_remaining() {
+        if body.remaining() < 4 {
+            return Err("truncated batch".to_owned());
+        }
+        let len = body.get_u32() as usize;
+        if body.remaining() < len {
+            return Err("truncated batch".to_owned());
+        }
+        messages.push(body.split_to(len));
+    }
+    Ok(messages)
+}
+
+impl Hydroflow {
+    /// Like [Self::inbound_tcp_vertex], but receives the batches sent by
+    /// [Self::outbound_tcp_vertex_batched], producing each message in them.
+    pub async fn inbound_tcp_vertex_batched<T>(&mut self) -> (u16, RecvPort<VecHandoff<T>>)
+    where
+        T: 'static + DeserializeOwned + Send,
+    {
+        self.inbound_tcp_vertex_batched_with_codec(None, BincodeCodec)
+            .await
+    }
+
+    /// Like [Self::inbound_tcp_vertex_batched], but listens on `port` if
+    /// given, and decodes messages with `codec`. Batches which can't be
+    /// unpacked, and messages which fail to decode, are dropped.
+    pub async fn inbound_tcp_vertex_batched_with_codec<T, C>(
+        &mut self,
+        port: Option<u16>,
+        codec: C,
+    ) -> (u16, RecvPort<VecHandoff<T>>)
+    where
+        T: 'static + Send,
+        C: Decode<T>,
+    {
+        let decode_message = decode_with(codec);
+        let decode = move |frame| match decode_batch(frame) {
+            Ok(messages) => messages.into_iter().filter_map(&decode_message).collect(),
+            Err(e) => {
+                eprintln!("couldn't unpack batch: {}", e);
+                Vec::new()
+            }
+        };
+        self.inbound_tcp_vertex_internal(port, futures::future::ok, decode)
+            .await
+    }
+
+    /// Like [Self::outbound_tcp_vertex], but batches messages to each
+    /// destination according to `config`. The receiving end must use
+    /// [Self::inbound_tcp_vertex_batched].
+    pub async fn outbound_tcp_vertex_batched<T>(
+        &mut self,
+        config: BatchConfig,
+    ) -> SendPort<VecHandoff<(Address, T)>>
+    where
+        T: 'static + Serialize + Send,
+    {
+        self.outbound_tcp_vertex_batched_with_codec(config, BincodeCodec)
+            .await
+    }
+
+    /// Like [Self::outbound_tcp_vertex_batched], but encodes messages with
+    /// `codec`. Messages which fail to encode are dropped.
+    pub async fn outbound_tcp_vertex_batched_with_codec<T, C>(
+        &mut self,
+        config: BatchConfig,
+        codec: C,
+    ) -> SendPort<VecHandoff<(Address, T)>>
+    where
+        T: 'static + Send,
+        C: Encode<T>,
+    {
+        let connection_manager = self.connection_manager().clone();
+        let mut batches = HashMap::<Address, Batch>::new();
+        // When the pending timer will wake this subgraph, if there is one.
+        let mut timer: Option<Instant> = None;
+
+        let (input_port, output_port) = self.make_edge("tcp batched egress handoff");
+        self.add_subgraph_sink("tcp batched egress", output_port, move |ctx, recv| {
+            let now = Instant::now();
+            if timer.map_or(false, |deadline| deadline <= now) {
+                timer = None;
+            }
+
+            for (addr, msg) in recv.take_inner() {
+                let msg = match codec.encode(&msg) {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        eprintln!("couldn't encode message: {}", e);
+                        continue;
+                    }
+                };
+                let addr: Address = addr;
+                let batch = batches.entry(addr.clone()).or_default();
+                batch.started.get_or_insert(now);
+                batch.bytes += msg.len();
+                batch.messages.push(msg);
+                if config.max_messages <= batch.messages.len() || config.max_bytes <= batch.bytes {
+                    let batch = std::mem::take(batch);
+                    connection_manager
+                        .send(addr, encode_batch(&batch.messages, config.compression));
+                }
+            }
+
+            // Send the batches which have waited long enough, and find when
+            // the next one will have.
+            let mut next_deadline: Option<Instant> = None;
+            for (addr, batch) in batches.iter_mut() {
+                let deadline = match batch.started {
+                    Some(started) => started + config.max_delay,
+                    None => continue,
+                };
+                if deadline <= now {
+                    let batch = std::mem::take(batch);
+                    connection_manager.send(
+                        addr.clone(),
+                        encode_batch(&batch.messages, config.compression),
+                    );
+                } else {
+                    next_deadline = Some(next_deadline.map_or(deadline, |d| d.min(deadline)));
+                }
+            }
+            batches.retain(|_, batch| batch.started.is_some());
+
+            if let Some(deadline) = next_deadline {
+                if timer.map_or(true, |pending| deadline < pending) {
+                    timer = Some(deadline);
+                    let waker = ctx.waker();
+                    tokio::spawn(async move {
+                        tokio::time::sleep_until(deadline.into()).await;
+                        waker.wake();
+                    });
+                }
+            }
+        });
+
+        input_port
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_round_trip() {
+        let messages: Vec<Bytes> = vec!["hello".into(), Bytes::new(), "world".into()];
+        for compression in [Compression::None, Compression::Lz4] {
+            assert_eq!(
+                Ok(messages.clone()),
+                decode_batch(encode_batch(&messages, compression))
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_batch_empty_frame() {
+        assert!(decode_batch(Bytes::new()).is_err());
+    }
+
+    #[test]
+    fn test_decode_batch_truncated() {
+        let frame = encode_batch(&["hello".into()], Compression::None);
+        // Cut off in the middle of the message, then of its length.
+        assert!(decode_batch(frame.slice(..frame.len() - 1)).is_err());
+        assert!(decode_batch(frame.slice(..3)).is_err());
+    }
+
+    #[test]
+    fn test_decode_batch_oversized() {
+        let mut frame = vec![FLAG_LZ4];
+        frame.extend_from_slice(&(MAX_BATCH_LEN as u32 + 1).to_le_bytes());
+        frame.extend_from_slice(&[0; 16]);
+        let error = decode_batch(frame.into()).unwrap_err();
+        assert!(error.contains("too large"), "{}", error);
+    }
+}
@@ -0,0 +1,119 @@
+//! Wire formats for network vertices.
+//!
+//! Network vertices frame each message with a 4-byte big-endian length prefix;
+//! a [`Codec`] determines how a message is turned into the bytes of a single
+//! frame and back.
+
+use std::convert::Infallible;
+
+use bytes::Bytes;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Converts messages of type `T` into the bytes of a single frame.
+pub trait Encode<T>: 'static + Clone + Send + Sync {
+    type Error: 'static + std::error::Error + Send + Sync;
+
+    fn encode(&self, item: &T) -> Result<Bytes, Self::Error>;
+}
+
+/// Converts the bytes of a single frame back into a message of type `T`.
+pub trait Decode<T>: 'static + Clone + Send + Sync {
+    type Error: 'static + std::error::Error + Send + Sync;
+
+    fn decode(&self, frame: Bytes) -> Result<T, Self::Error>;
+}
+
+/// A wire format for messages of type `T`, able to both [`Encode`] and
+/// [`Decode`] them.
+pub trait Codec<T>: Encode<T> + Decode<T> {}
+impl<T, C> Codec<T> for C where C: Encode<T> + Decode<T> {}
+
+/// Encodes messages with [bincode](https://docs.rs/bincode). This is the
+/// default for Rust-to-Rust communication.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BincodeCodec;
+
+impl<T> Encode<T> for BincodeCodec
+where
+    T: Serialize,
+{
+    type Error = bincode::Error;
+
+    fn encode(&self, item: &T) -> Result<Bytes, Self::Error> {
+        bincode::serialize(item).map(Into::into)
+    }
+}
+
+impl<T> Decode<T> for BincodeCodec
+where
+    T: DeserializeOwned,
+{
+    type Error = bincode::Error;
+
+    fn decode(&self, frame: Bytes) -> Result<T, Self::Error> {
+        bincode::deserialize(&frame)
+    }
+}
+
+/// Encodes each message as a UTF-8 JSON document.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonCodec;
+
+impl<T> Encode<T> for JsonCodec
+where
+    T: Serialize,
+{
+    type Error = serde_json::Error;
+
+    fn encode(&self, item: &T) -> Result<Bytes, Self::Error> {
+        serde_json::to_vec(item).map(Into::into)
+    }
+}
+
+impl<T> Decode<T> for JsonCodec
+where
+    T: DeserializeOwned,
+{
+    type Error = serde_json::Error;
+
+    fn decode(&self, frame: Bytes) -> Result<T, Self::Error> {
+        serde_json::from_slice(&frame)
+    }
+}
+
+/// Passes frames through as raw bytes, so the wire format is just the
+/// length-prefixed payload.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RawBytesCodec;
+
+impl Encode<Bytes> for RawBytesCodec {
+    type Error = Infallible;
+
+    fn encode(&self, item: &Bytes) -> Result<Bytes, Self::Error> {
+        Ok(item.clone())
+    }
+}
+
+impl Decode<Bytes> for RawBytesCodec {
+    type Error = Infallible;
+
+    fn decode(&self, frame: Bytes) -> Result<Bytes, Self::Error> {
+        Ok(frame)
+    }
+}
+
+impl Encode<Vec<u8>> for RawBytesCodec {
+    type Error = Infallible;
+
+    fn encode(&self, item: &Vec<u8>) -> Result<Bytes, Self::Error> {
+        Ok(Bytes::copy_from_slice(item))
+    }
+}
+
+impl Decode<Vec<u8>> for RawBytesCodec {
+    type Error = Infallible;
+
+    fn decode(&self, frame: Bytes) -> Result<Vec<u8>, Self::Error> {
+        Ok(frame.to_vec())
+    }
+}
@@ -0,0 +1,380 @@
+//! A per-[`Hydroflow`](crate::scheduled::graph::Hydroflow) cache of TCP
+//! connections used for sending.
+//!
+//! Outbound vertices send length-delimited frames through the
+//! [`ConnectionManager`], which keeps one connection open per address.
+//! Connections accepted by inbound vertices are registered under the peer's
+//! socket address, so sending to that address reuses the inbound connection
+//! instead of opening a new one. Once such a connection closes, messages sent
+//! to its address are dropped: the peer's port is usually ephemeral, so it is
+//! never connected to.
+//!
+//! Failed connections are retried with exponential backoff, and messages stay
+//! queued while reconnecting. Delivery is at-least-once while reconnecting: a
+//! frame whose write failed is sent again on the new connection, so the peer
+//! may receive it twice. Queued messages are dropped if the connection gives
+//! up, see [`Backoff::max_attempts`].
+//!
+//! Background tasks only hold weak references to the manager, so once every
+//! clone of it has been dropped (e.g. along with its
+//! [`Hydroflow`](crate::scheduled::graph::Hydroflow)), its outbound
+//! connections are closed.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures::{SinkExt, StreamExt};
+use tokio::io::AsyncWrite;
+use tokio::net::TcpStream;
+use tokio_util::codec::{FramedWrite, LengthDelimitedCodec};
+
+use super::network_vertex::Address;
+
+/// How long to wait between attempts to (re)connect.
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    /// Delay after the first failed attempt.
+    pub initial: Duration,
+    /// The delay doubles after each failed attempt, up to this limit.
+    pub max: Duration,
+    /// Give up after this many consecutive failed attempts, dropping any
+    /// queued messages. `None` retries forever, while messages queue without
+    /// bound.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for Backoff {
+    /// Gives up after 12 attempts, about 15 seconds of delays in total.
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(10),
+            max: Duration::from_secs(5),
+            max_attempts: Some(12),
+        }
+    }
+}
+
+/// How many closed inbound connections are remembered, so their addresses
+/// aren't connected to.
+const MAX_CLOSED_INBOUND: usize = 1024;
+
+/// The state of the connection to one address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionHealth {
+    /// Attempting to connect, after `attempts` consecutive failed attempts.
+    Connecting {
+        attempts: u32,
+    },
+    Connected,
+    /// Waiting to reconnect after `attempts` consecutive failed attempts, the
+    /// last of which failed with `error`.
+    Backoff {
+        attempts: u32,
+        error: String,
+    },
+    /// Gave up after [`Backoff::max_attempts`] and dropped the queued
+    /// messages. The next message sent to this address will try again.
+    Failed {
+        error: String,
+    },
+    /// An inbound connection from this address was closed. Messages sent to
+    /// it are dropped.
+    Closed,
+}
+
+struct Connection {
+    /// Distinguishes this connection from later ones to the same address.
+    id: u64,
+    /// Whether this connection was accepted from the address.
+    inbound: bool,
+    sender: UnboundedSender<Bytes>,
+    health: ConnectionHealth,
+}
+
+#[derive(Default)]
+struct Inner {
+    backoff: Backoff,
+    next_id: u64,
+    connections: HashMap<Address, Connection>,
+    /// Addresses of closed inbound connections, oldest first.
+    closed_inbound: VecDeque<Address>,
+}
+
+impl Inner {
+    fn close_inbound(&mut self, address: Address, id: u64) {
+        match self.connections.get_mut(&address) {
+            Some(conn) if conn.id == id => {
+                conn.sender.close_channel();
+                conn.health = ConnectionHealth::Closed;
+            }
+            _ => return,
+        }
+        self.closed_inbound.push_back(address);
+        if MAX_CLOSED_INBOUND < self.closed_inbound.len() {
+            let oldest = self.closed_inbound.pop_front().unwrap();
+            // Unless it has since been reconnected.
+            if self.connections.get(&oldest).map(|conn| &conn.health)
+                == Some(&ConnectionHealth::Closed)
+            {
+                self.connections.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Shared cache of connections, keyed by address. Cheap to clone.
+#[derive(Clone, Default)]
+pub struct ConnectionManager {
+    inner: Arc<Mutex<Inner>>,
+    /// Wraps each outbound connection in TLS if set.
+    #[cfg(feature = "tls")]
+    tls: Option<tokio_rustls::TlsConnector>,
+}
+
+/// A [`ConnectionManager`] which background tasks can hold without keeping it
+/// alive.
+#[derive(Clone)]
+pub(crate) struct WeakConnectionManager {
+    inner: Weak<Mutex<Inner>>,
+    #[cfg(feature = "tls")]
+    tls: Option<tokio_rustls::TlsConnector>,
+}
+
+impl WeakConnectionManager {
+    pub(crate) fn upgrade(&self) -> Option<ConnectionManager> {
+        Some(ConnectionManager {
+            inner: self.inner.upgrade()?,
+            #[cfg(feature = "tls")]
+            tls: self.tls.clone(),
+        })
+    }
+}
+
+/// An inbound connection registered with [ConnectionManager::register_inbound],
+/// which is marked closed when this is dropped.
+pub(crate) struct InboundRegistration {
+    inner: Weak<Mutex<Inner>>,
+    address: Address,
+    id: u64,
+}
+
+impl Drop for InboundRegistration {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.upgrade() {
+            let address = std::mem::take(&mut self.address);
+            inner.lock().unwrap().close_inbound(address, self.id);
+        }
+    }
+}
+
+impl ConnectionManager {
+    /// Creates a manager whose outbound connections all use TLS with
+    /// `config`. The server name checked against each server's certificate is
+    /// the host part of the address being connected to.
+    #[cfg(feature = "tls")]
+    pub fn with_tls(config: Arc<tokio_rustls::rustls::ClientConfig>) -> Self {
+        Self {
+            inner: Default::default(),
+            tls: Some(config.into()),
+        }
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakConnectionManager {
+        WeakConnectionManager {
+            inner: Arc::downgrade(&self.inner),
+            #[cfg(feature = "tls")]
+            tls: self.tls.clone(),
+        }
+    }
+
+    /// Sets the backoff policy for connections opened from now on.
+    pub fn set_backoff(&self, backoff: Backoff) {
+        self.inner.lock().unwrap().backoff = backoff;
+    }
+
+    /// Returns the health of the connection to `address`, if there is one.
+    pub fn health(&self, address: &str) -> Option<ConnectionHealth> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .connections
+            .get(address)
+            .map(|conn| conn.health.clone())
+    }
+
+    /// Returns the health of all known connections.
+    pub fn health_all(&self) -> Vec<(Address, ConnectionHealth)> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .connections
+            .iter()
+            .map(|(address, conn)| (address.clone(), conn.health.clone()))
+            .collect()
+    }
+
+    /// Queues `frame` to be sent to `address`, connecting if needed. Must be
+    /// called from within a Tokio runtime.
+    pub fn send(&self, address: Address, frame: Bytes) {
+        let mut inner = self.inner.lock().unwrap();
+        let frame = match inner.connections.get(&address) {
+            Some(conn) => match conn.sender.unbounded_send(frame) {
+                Ok(()) => return,
+                Err(_) if conn.inbound => {
+                    eprintln!("connection from {} is closed, dropping message", address);
+                    return;
+                }
+                // The connection's task gave up, start a new one.
+                Err(e) => e.into_inner(),
+            },
+            None => frame,
+        };
+
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        sender.unbounded_send(frame).unwrap();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.connections.insert(
+            address.clone(),
+            Connection {
+                id,
+                inbound: false,
+                sender,
+                health: ConnectionHealth::Connecting { attempts: 0 },
+            },
+        );
+        let backoff = inner.backoff.clone();
+        tokio::spawn(
+            self.downgrade()
+                .run_outbound(address, id, receiver, backoff),
+        );
+    }
+
+    /// Registers an accepted connection from `peer`, whose writer is fed by
+    /// `sender`, until the returned registration is dropped.
+    pub(crate) fn register_inbound(
+        &self,
+        peer: SocketAddr,
+        sender: UnboundedSender<Bytes>,
+    ) -> InboundRegistration {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        let address = peer.to_string();
+        inner.connections.insert(
+            address.clone(),
+            Connection {
+                id,
+                inbound: true,
+                sender,
+                health: ConnectionHealth::Connected,
+            },
+        );
+        InboundRegistration {
+            inner: Arc::downgrade(&self.inner),
+            address,
+            id,
+        }
+    }
+}
+
+impl WeakConnectionManager {
+    /// Sets the health of connection `id`, returning false if the manager has
+    /// been dropped.
+    fn set_health(&self, address: &str, id: u64, health: ConnectionHealth) -> bool {
+        let inner = match self.inner.upgrade() {
+            Some(inner) => inner,
+            None => return false,
+        };
+        let mut inner = inner.lock().unwrap();
+        if let Some(conn) = inner.connections.get_mut(address) {
+            if conn.id == id {
+                conn.health = health;
+            }
+        }
+        true
+    }
+
+    async fn connect(&self, address: &str) -> std::io::Result<Box<dyn AsyncWrite + Send + Unpin>> {
+        let stream = TcpStream::connect(address).await?;
+        #[cfg(feature = "tls")]
+        if let Some(connector) = &self.tls {
+            return Ok(Box::new(
+                super::tls::connect(connector, address, stream).await?,
+            ));
+        }
+        Ok(Box::new(stream))
+    }
+
+    /// Connects to `address` and writes frames from `receiver` to it,
+    /// reconnecting with backoff whenever connecting or writing fails.
+    async fn run_outbound(
+        self,
+        address: Address,
+        id: u64,
+        mut receiver: UnboundedReceiver<Bytes>,
+        backoff: Backoff,
+    ) {
+        // A frame which failed to write, to be retried first.
+        let mut pending = None;
+        let mut attempts = 0;
+        let mut delay = backoff.initial;
+        loop {
+            let error = match self.connect(&address).await {
+                Ok(stream) => {
+                    attempts = 0;
+                    delay = backoff.initial;
+                    if !self.set_health(&address, id, ConnectionHealth::Connected) {
+                        return;
+                    }
+
+                    let mut writer = FramedWrite::new(stream, LengthDelimitedCodec::new());
+                    loop {
+                        let frame = match pending.take() {
+                            Some(frame) => frame,
+                            None => match receiver.next().await {
+                                Some(frame) => frame,
+                                // The manager was dropped.
+                                None => return,
+                            },
+                        };
+                        if let Err(e) = writer.send(Bytes::clone(&frame)).await {
+                            pending = Some(frame);
+                            break e;
+                        }
+                    }
+                }
+                Err(e) => e,
+            };
+
+            attempts += 1;
+            if backoff.max_attempts.map_or(false, |max| max <= attempts) {
+                // Close first so later sends start a new connection rather
+                // than queueing here. Queued messages are dropped.
+                receiver.close();
+                self.set_health(
+                    &address,
+                    id,
+                    ConnectionHealth::Failed {
+                        error: error.to_string(),
+                    },
+                );
+                return;
+            }
+            let backing_off = ConnectionHealth::Backoff {
+                attempts,
+                error: error.to_string(),
+            };
+            if !self.set_health(&address, id, backing_off) {
+                return;
+            }
+            tokio::time::sleep(delay).await;
+            delay = std::cmp::min(2 * delay, backoff.max);
+            if !self.set_health(&address, id, ConnectionHealth::Connecting { attempts }) {
+                return;
+            }
+        }
+    }
+}
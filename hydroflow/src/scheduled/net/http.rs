@@ -0,0 +1,184 @@
+//! HTTP/1.1 ingress and egress, for serving requests from a flow. Only
+//! available with the `http` feature.
+//!
+//! [Hydroflow::add_http_server] yields each request along with a
+//! [`ResponderHandle`], which the flow answers by sending it, together with
+//! the response, to [Hydroflow::add_http_responder]. Request bodies are read
+//! in full before the request enters the flow, and requests with bodies
+//! longer than [`MAX_BODY_LEN`] get `413 Payload Too Large`. A request whose
+//! handle is dropped without being answered gets an empty
+//! `500 Internal Server Error`.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use bytes::{Bytes, BytesMut};
+use futures::channel::mpsc::Sender;
+use futures::channel::oneshot;
+use futures::{SinkExt, StreamExt};
+use hyper::body::HttpBody;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Server};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::network_vertex::INGRESS_BUFFER;
+use crate::scheduled::{
+    graph::Hydroflow,
+    graph_ext::GraphExt,
+    handoff::VecHandoff,
+    port::{RecvPort, SendPort},
+};
+
+pub use hyper::{header, Method, StatusCode};
+
+/// Longest request body accepted, in bytes.
+pub const MAX_BODY_LEN: usize = 1 << 20;
+
+/// A request with its body read in full.
+pub type Request = hyper::Request<Bytes>;
+pub type Response = hyper::Response<Bytes>;
+
+/// Answers one [`Request`] from [Hydroflow::add_http_server].
+#[derive(Debug)]
+pub struct ResponderHandle {
+    sender: oneshot::Sender<Response>,
+}
+
+impl ResponderHandle {
+    /// Sends `response` to the client. Does nothing if the client has already
+    /// disconnected.
+    pub fn respond(self, response: Response) {
+        let _ = self.sender.send(response);
+    }
+}
+
+/// Deserializes the JSON body of `request`.
+pub fn json_body<T>(request: &Request) -> serde_json::Result<T>
+where
+    T: DeserializeOwned,
+{
+    serde_json::from_slice(request.body())
+}
+
+/// A response with status `status` and `value` serialized as its JSON body.
+pub fn json_response<T>(status: StatusCode, value: &T) -> serde_json::Result<Response>
+where
+    T: Serialize,
+{
+    let body = serde_json::to_vec(value)?;
+    Ok(hyper::Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body.into())
+        .unwrap())
+}
+
+/// A response with status `status` and an empty body.
+pub fn status_response(status: StatusCode) -> Response {
+    let mut response = Response::default();
+    *response.status_mut() = status;
+    response
+}
+
+/// Reads `body` in full, or returns `None` as soon as it is known to be
+/// longer than [`MAX_BODY_LEN`].
+async fn read_body(mut body: Body) -> hyper::Result<Option<Bytes>> {
+    if (MAX_BODY_LEN as u64) < body.size_hint().lower() {
+        return Ok(None);
+    }
+    let mut buf = BytesMut::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if MAX_BODY_LEN < buf.len() + chunk.len() {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok(Some(buf.freeze()))
+}
+
+/// Reads the body of `request` and passes it to the flow, then waits for the
+/// flow to answer it.
+async fn handle(
+    request: hyper::Request<Body>,
+    mut incoming_send: Sender<(Request, ResponderHandle)>,
+) -> Result<hyper::Response<Body>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = match read_body(body).await {
+        Ok(Some(body)) => body,
+        Ok(None) => {
+            return Ok(status_response(StatusCode::PAYLOAD_TOO_LARGE).map(Body::from));
+        }
+        Err(e) => {
+            eprintln!("couldn't read request body: {}", e);
+            return Ok(status_response(StatusCode::BAD_REQUEST).map(Body::from));
+        }
+    };
+
+    let (sender, receiver) = oneshot::channel();
+    let request = Request::from_parts(parts, body);
+    if incoming_send
+        .send((request, ResponderHandle { sender }))
+        .await
+        .is_err()
+    {
+        // The flow has been dropped.
+        return Ok(status_response(StatusCode::SERVICE_UNAVAILABLE).map(Body::from));
+    }
+
+    let response = receiver
+        .await
+        .unwrap_or_else(|_canceled| status_response(StatusCode::INTERNAL_SERVER_ERROR));
+    Ok(response.map(Body::from))
+}
+
+impl Hydroflow {
+    /// Serves HTTP on `addr`, returning the address actually bound (so port 0
+    /// picks any free port) and the requests received. Each must be answered
+    /// through its [`ResponderHandle`]. Must be called from within a Tokio
+    /// runtime.
+    pub fn add_http_server(
+        &mut self,
+        addr: SocketAddr,
+    ) -> (SocketAddr, RecvPort<VecHandoff<(Request, ResponderHandle)>>) {
+        let listener = std::net::TcpListener::bind(addr).unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (incoming_send, incoming_requests) = futures::channel::mpsc::channel(INGRESS_BUFFER);
+
+        let make_service = make_service_fn(move |_conn| {
+            let incoming_send = incoming_send.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    handle(request, incoming_send.clone())
+                }))
+            }
+        });
+        let server = Server::from_tcp(listener).unwrap().serve(make_service);
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                eprintln!("http server failed: {}", e);
+            }
+        });
+
+        let (send_port, recv_port) = self.make_edge("http ingress handoff");
+        self.add_input_from_stream("http ingress", send_port, incoming_requests.map(Some));
+
+        (addr, recv_port)
+    }
+
+    /// Answers each request by sending its [`ResponderHandle`] the paired
+    /// response.
+    pub fn add_http_responder(&mut self) -> SendPort<VecHandoff<(ResponderHandle, Response)>> {
+        let (input_port, output_port) = self.make_edge("http egress handoff");
+        self.add_subgraph_sink("http egress", output_port, |_ctx, recv| {
+            for (responder, response) in recv.take_inner() {
+                let responder: ResponderHandle = responder;
+                responder.respond(response);
+            }
+        });
+
+        input_port
+    }
+}
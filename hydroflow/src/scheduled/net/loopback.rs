@@ -0,0 +1,146 @@
+//! An in-process network which mimics the TCP network vertices without
+//! binding any sockets.
+//!
+//! Instances (in any threads) sharing a [`LoopbackNetwork`] can address each
+//! other's inbound vertices just like TCP addresses. Messages are still
+//! encoded on send and decoded on receipt, but each message is delivered
+//! immediately, exactly once, and in order per sender. This makes multi-node
+//! tests deterministic, and lets them step several [`Hydroflow`] instances in
+//! a single thread with [`Hydroflow::tick`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures::StreamExt;
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::codec::{BincodeCodec, Decode, Encode};
+use super::network_vertex::{decode_with, Address};
+use crate::scheduled::{
+    graph::Hydroflow,
+    graph_ext::GraphExt,
+    handoff::VecHandoff,
+    port::{RecvPort, SendPort},
+};
+
+#[derive(Default)]
+struct LoopbackInner {
+    next_port: u64,
+    endpoints: HashMap<Address, UnboundedSender<Bytes>>,
+}
+
+/// A shared in-process network. Cheap to clone.
+#[derive(Clone, Default)]
+pub struct LoopbackNetwork {
+    inner: Arc<Mutex<LoopbackInner>>,
+}
+
+impl LoopbackNetwork {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Binds `address`, or a fresh `loopback:<n>` address if `None`.
+    fn bind(&self, address: Option<Address>) -> (Address, UnboundedReceiver<Bytes>) {
+        let mut inner = self.inner.lock().unwrap();
+        let address = address.unwrap_or_else(|| {
+            inner.next_port += 1;
+            format!("loopback:{}", inner.next_port)
+        });
+        let (send, recv) = futures::channel::mpsc::unbounded();
+        assert!(
+            inner.endpoints.insert(address.clone(), send).is_none(),
+            "Loopback address {} is already bound.",
+            address
+        );
+        (address, recv)
+    }
+
+    /// Delivers `frame` to `address`, returning `false` if nothing is bound there.
+    fn send(&self, address: &str, frame: Bytes) -> bool {
+        let inner = self.inner.lock().unwrap();
+        match inner.endpoints.get(address) {
+            Some(endpoint) => endpoint.unbounded_send(frame).is_ok(),
+            None => false,
+        }
+    }
+}
+
+impl Hydroflow {
+    /// Like [Self::inbound_tcp_vertex], but listens on a fresh address of
+    /// `network` instead of a TCP port.
+    pub fn inbound_loopback_vertex<T>(
+        &mut self,
+        network: &LoopbackNetwork,
+    ) -> (Address, RecvPort<VecHandoff<T>>)
+    where
+        T: 'static + DeserializeOwned,
+    {
+        self.inbound_loopback_vertex_internal(network, None, BincodeCodec)
+    }
+
+    /// Like [Self::inbound_loopback_vertex], but listens on `address`.
+    /// Panics if `address` is already bound.
+    pub fn inbound_loopback_vertex_at<T>(
+        &mut self,
+        network: &LoopbackNetwork,
+        address: Address,
+    ) -> RecvPort<VecHandoff<T>>
+    where
+        T: 'static + DeserializeOwned,
+    {
+        self.inbound_loopback_vertex_internal(network, Some(address), BincodeCodec)
+            .1
+    }
+
+    fn inbound_loopback_vertex_internal<T, C>(
+        &mut self,
+        network: &LoopbackNetwork,
+        address: Option<Address>,
+        codec: C,
+    ) -> (Address, RecvPort<VecHandoff<T>>)
+    where
+        T: 'static,
+        C: Decode<T>,
+    {
+        let (address, frames) = network.bind(address);
+        let messages = frames.map(decode_with(codec));
+
+        let (send_port, recv_port) = self.make_edge("loopback ingress handoff");
+        self.add_input_from_stream("loopback ingress", send_port, messages);
+        (address, recv_port)
+    }
+
+    /// Like [Self::outbound_tcp_vertex], but sends to addresses of `network`.
+    /// Messages to addresses which are not bound are dropped.
+    pub fn outbound_loopback_vertex<T>(
+        &mut self,
+        network: &LoopbackNetwork,
+    ) -> SendPort<VecHandoff<(Address, T)>>
+    where
+        T: 'static + Serialize,
+    {
+        let network = network.clone();
+        let codec = BincodeCodec;
+        let (input_port, output_port) = self.make_edge("loopback egress handoff");
+        self.add_subgraph_sink("loopback egress", output_port, move |_ctx, recv| {
+            for (addr, msg) in recv.take_inner() {
+                let addr: Address = addr;
+                let msg = match Encode::<T>::encode(&codec, &msg) {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        eprintln!("couldn't encode message: {}", e);
+                        continue;
+                    }
+                };
+                if !network.send(&addr, msg) {
+                    eprintln!("couldn't connect to {}: nothing bound", addr);
+                }
+            }
+        });
+
+        input_port
+    }
+}
@@ -59,10 +59,8 @@ use std::pin::Pin;
 
 use byteorder::{NetworkEndian, WriteBytesExt};
 use futures::{Sink, StreamExt};
-use tokio::net::{
-    tcp::{OwnedReadHalf, OwnedWriteHalf},
-    TcpStream,
-};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
 use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
 
 use super::{
@@ -72,7 +70,20 @@ use super::{
     port::{RecvPort, SendPort},
 };
 
+pub mod batching;
+pub mod codec;
+pub mod connection_manager;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod loopback;
+pub mod mux;
 pub mod network_vertex;
+pub mod simulation;
+#[cfg(feature = "tls")]
+pub mod tls;
+mod udp;
+#[cfg(unix)]
+mod unix;
 
 const ADDRESS_LEN: usize = 4;
 
@@ -98,7 +109,10 @@ impl Message {
 }
 
 impl Hydroflow {
-    fn register_read_tcp_stream(&mut self, reader: OwnedReadHalf) -> RecvPort<VecHandoff<Message>> {
+    fn register_read_tcp_stream<R>(&mut self, reader: R) -> RecvPort<VecHandoff<Message>>
+    where
+        R: 'static + AsyncRead + Unpin,
+    {
         let reader = FramedRead::new(reader, LengthDelimitedCodec::new());
         let (send_port, recv_port) = self.make_edge("tcp ingress handoff");
         self.add_input_from_stream(
@@ -109,10 +123,10 @@ impl Hydroflow {
         recv_port
     }
 
-    fn register_write_tcp_stream(
-        &mut self,
-        writer: OwnedWriteHalf,
-    ) -> SendPort<VecHandoff<Message>> {
+    fn register_write_tcp_stream<W>(&mut self, writer: W) -> SendPort<VecHandoff<Message>>
+    where
+        W: 'static + AsyncWrite + Unpin,
+    {
         let mut writer = FramedWrite::new(writer, LengthDelimitedCodec::new());
         let mut message_queue = VecDeque::new();
 
@@ -0,0 +1,295 @@
+//! Multiplexes several typed logical channels over one TCP connection.
+//!
+//! Each frame on the connection carries a header naming the [`Channel`] it
+//! belongs to and a fingerprint of the channel's type tag, followed by the
+//! encoded message:
+//!
+//! ```text
+//! | channel id: u32 | tag fingerprint: u64 | payload ... |
+//! ```
+//!
+//! Both ends register the channels they use with [`TcpMux::channel`]. A frame
+//! whose fingerprint doesn't match the tag registered for its channel on the
+//! receiving end is dropped, so a mismatch between the two ends is reported
+//! rather than misdecoded. Frames for a channel which hasn't been registered
+//! yet are buffered until it is, up to [`MAX_PENDING_PER_CHANNEL`] per
+//! channel and [`MAX_PENDING`] in total.
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+use byteorder::{NetworkEndian, WriteBytesExt};
+use bytes::Bytes;
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures::StreamExt;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::net::TcpStream;
+use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
+
+use super::codec::{BincodeCodec, Decode, Encode};
+use super::network_vertex::decode_with;
+use crate::lang::partitioner::stable_hash;
+use crate::scheduled::{
+    graph::Hydroflow,
+    graph_ext::GraphExt,
+    handoff::VecHandoff,
+    port::{RecvPort, SendPort},
+};
+
+const HEADER_LEN: usize = 12;
+
+/// Most frames buffered for a single unregistered channel.
+pub const MAX_PENDING_PER_CHANNEL: usize = 1024;
+/// Most frames buffered for all unregistered channels together.
+pub const MAX_PENDING: usize = 16 * 1024;
+
+/// A logical channel carrying messages of type `T`. Typically declared as a
+/// constant shared by both ends of the connection:
+///
+/// ```
+/// # use hydroflow::scheduled::net::mux::Channel;
+/// const GREETINGS: Channel<String> = Channel::new(0, "greeting/v1");
+/// ```
+///
+/// The `tag` names the message type and its encoding. Both ends must use the
+/// same tag for a channel, and should change it whenever the message type
+/// changes incompatibly.
+pub struct Channel<T> {
+    id: u32,
+    tag: &'static str,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> Channel<T> {
+    pub const fn new(id: u32, tag: &'static str) -> Self {
+        Self {
+            id,
+            tag,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn tag(&self) -> &'static str {
+        self.tag
+    }
+
+    fn fingerprint(&self) -> u64 {
+        stable_hash(self.tag)
+    }
+}
+
+impl<T> Clone for Channel<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for Channel<T> {}
+
+#[derive(Default)]
+struct Routes {
+    /// The fingerprint and receiver of each registered channel.
+    channels: HashMap<u32, (u64, UnboundedSender<Bytes>)>,
+    /// Frames (with their fingerprint) received for unregistered channels.
+    pending: HashMap<u32, Vec<(u64, Bytes)>>,
+    /// The number of frames in `pending`.
+    pending_len: usize,
+}
+
+impl Routes {
+    fn route(&mut self, id: u32, fingerprint: u64, payload: Bytes) {
+        match self.channels.get(&id) {
+            Some((expected, sender)) => {
+                if *expected == fingerprint {
+                    let _ = sender.unbounded_send(payload);
+                } else {
+                    eprintln!(
+                        "dropping message on channel {}: sent with a different type than registered",
+                        id
+                    );
+                }
+            }
+            None => {
+                let buffered = self.pending.get(&id).map_or(0, Vec::len);
+                if MAX_PENDING_PER_CHANNEL <= buffered || MAX_PENDING <= self.pending_len {
+                    eprintln!(
+                        "dropping message on unregistered channel {}: too many buffered",
+                        id
+                    );
+                    return;
+                }
+                self.pending
+                    .entry(id)
+                    .or_default()
+                    .push((fingerprint, payload));
+                self.pending_len += 1;
+            }
+        }
+    }
+
+    /// Takes the frames buffered for channel `id`.
+    fn take_pending(&mut self, id: u32) -> Vec<(u64, Bytes)> {
+        let pending = self.pending.remove(&id).unwrap_or_default();
+        self.pending_len -= pending.len();
+        pending
+    }
+}
+
+/// A TCP connection carrying several [`Channel`]s, as returned by
+/// [Hydroflow::add_tcp_mux]. Cheap to clone.
+#[derive(Clone)]
+pub struct TcpMux {
+    routes: Arc<Mutex<Routes>>,
+    writer: UnboundedSender<Bytes>,
+}
+
+impl Hydroflow {
+    /// Multiplexes `stream`, so [`Channel`]s can be registered on it with
+    /// [TcpMux::channel]. Must be called from within a Tokio runtime.
+    pub fn add_tcp_mux(&mut self, stream: TcpStream) -> TcpMux {
+        let (reader, writer) = stream.into_split();
+        let routes = Arc::new(Mutex::new(Routes::default()));
+
+        let read_routes = routes.clone();
+        tokio::spawn(async move {
+            let mut reader = FramedRead::new(reader, LengthDelimitedCodec::new());
+            while let Some(Ok(frame)) = reader.next().await {
+                if frame.len() < HEADER_LEN {
+                    eprintln!("dropping truncated multiplexed frame");
+                    continue;
+                }
+                let frame = frame.freeze();
+                let id = u32::from_be_bytes(frame[0..4].try_into().unwrap());
+                let fingerprint = u64::from_be_bytes(frame[4..HEADER_LEN].try_into().unwrap());
+                read_routes
+                    .lock()
+                    .unwrap()
+                    .route(id, fingerprint, frame.slice(HEADER_LEN..));
+            }
+        });
+
+        let (write_send, write_recv) = futures::channel::mpsc::unbounded();
+        let writer = FramedWrite::new(writer, LengthDelimitedCodec::new());
+        tokio::spawn(write_recv.map(Ok).forward(writer));
+
+        TcpMux {
+            routes,
+            writer: write_send,
+        }
+    }
+}
+
+impl TcpMux {
+    /// Registers `channel` with `hydroflow`, returning ports to send messages
+    /// to the other end's channel of the same id, and to receive messages from
+    /// it. Panics if the channel is already registered on this connection.
+    pub fn channel<T>(
+        &self,
+        hydroflow: &mut Hydroflow,
+        channel: Channel<T>,
+    ) -> (SendPort<VecHandoff<T>>, RecvPort<VecHandoff<T>>)
+    where
+        T: 'static + Serialize + DeserializeOwned,
+    {
+        self.channel_with_codec(hydroflow, channel, BincodeCodec)
+    }
+
+    /// Like [Self::channel], but encodes messages with `codec`.
+    pub fn channel_with_codec<T, C>(
+        &self,
+        hydroflow: &mut Hydroflow,
+        channel: Channel<T>,
+        codec: C,
+    ) -> (SendPort<VecHandoff<T>>, RecvPort<VecHandoff<T>>)
+    where
+        T: 'static,
+        C: Encode<T> + Decode<T>,
+    {
+        let fingerprint = channel.fingerprint();
+        let frames = self.register(channel.id, fingerprint);
+
+        let messages = frames.map(decode_with(codec.clone()));
+        let (ingress_send, ingress_recv) =
+            hydroflow.make_edge(format!("mux channel {} ingress handoff", channel.id));
+        hydroflow.add_input_from_stream(
+            format!("mux channel {} ingress", channel.id),
+            ingress_send,
+            messages,
+        );
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.write_u32::<NetworkEndian>(channel.id).unwrap();
+        header.write_u64::<NetworkEndian>(fingerprint).unwrap();
+        let writer = self.writer.clone();
+        let (egress_send, egress_recv) =
+            hydroflow.make_edge(format!("mux channel {} egress handoff", channel.id));
+        hydroflow.add_subgraph_sink(
+            format!("mux channel {} egress", channel.id),
+            egress_recv,
+            move |_ctx, recv| {
+                for msg in recv.take_inner() {
+                    match codec.encode(&msg) {
+                        Ok(payload) => {
+                            let mut frame = header.clone();
+                            frame.extend_from_slice(&payload);
+                            let _ = writer.unbounded_send(frame.into());
+                        }
+                        Err(e) => eprintln!("couldn't encode message: {}", e),
+                    }
+                }
+            },
+        );
+
+        (egress_send, ingress_recv)
+    }
+
+    /// Routes frames for channel `id` to the returned receiver, starting with
+    /// any which arrived before it was registered.
+    fn register(&self, id: u32, fingerprint: u64) -> UnboundedReceiver<Bytes> {
+        let mut routes = self.routes.lock().unwrap();
+        let (send, recv) = futures::channel::mpsc::unbounded();
+        assert!(
+            routes.channels.insert(id, (fingerprint, send)).is_none(),
+            "Channel {} is already registered.",
+            id
+        );
+        for (fingerprint, payload) in routes.take_pending(id) {
+            routes.route(id, fingerprint, payload);
+        }
+        recv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_limits() {
+        let mut routes = Routes::default();
+        for _ in 0..MAX_PENDING_PER_CHANNEL + 1 {
+            routes.route(0, 0, Bytes::new());
+        }
+        assert_eq!(MAX_PENDING_PER_CHANNEL, routes.pending[&0].len());
+
+        for id in 1.. {
+            if MAX_PENDING <= routes.pending_len {
+                break;
+            }
+            for _ in 0..MAX_PENDING_PER_CHANNEL {
+                routes.route(id, 0, Bytes::new());
+            }
+        }
+        assert_eq!(MAX_PENDING, routes.pending_len);
+        routes.route(u32::MAX, 0, Bytes::new());
+        assert_eq!(MAX_PENDING, routes.pending_len);
+        assert!(!routes.pending.contains_key(&u32::MAX));
+
+        assert_eq!(MAX_PENDING_PER_CHANNEL, routes.take_pending(0).len());
+        assert_eq!(MAX_PENDING - MAX_PENDING_PER_CHANNEL, routes.pending_len);
+    }
+}
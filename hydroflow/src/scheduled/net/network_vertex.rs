@@ -1,10 +1,17 @@
 use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 
-use futures::{SinkExt, StreamExt};
+use bytes::Bytes;
+use futures::{channel::mpsc::UnboundedSender, future, SinkExt, StreamExt};
 use serde::{de::DeserializeOwned, Serialize};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::{TcpListener, TcpStream};
 use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
 
+use super::codec::{BincodeCodec, Decode, Encode};
+use super::connection_manager::ConnectionManager;
 use crate::scheduled::{
     graph::Hydroflow,
     graph_ext::GraphExt,
@@ -14,6 +21,40 @@ use crate::scheduled::{
 
 pub type Address = String;
 
+/// Messages buffered between an inbound vertex's connections and the flow.
+/// Connections stop being read while the buffer is full.
+pub(super) const INGRESS_BUFFER: usize = 1024;
+
+/// Identifies one connection accepted by [Hydroflow::inbound_tcp_connections].
+/// Ids are never reused within a vertex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ConnectionId(pub u64);
+
+/// A connection being opened or closed on an inbound TCP vertex.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    Connected(ConnectionId, SocketAddr),
+    Disconnected(ConnectionId),
+}
+
+/// The ports of an inbound TCP vertex which tracks connection identity, as
+/// returned by [Hydroflow::inbound_tcp_connections].
+pub struct InboundTcpConnections<In, Out>
+where
+    In: 'static,
+    Out: 'static,
+{
+    /// The port being listened on.
+    pub port: u16,
+    /// Messages received, tagged with the connection they arrived on.
+    pub messages: RecvPort<VecHandoff<(ConnectionId, In)>>,
+    /// Connect and disconnect events.
+    pub events: RecvPort<VecHandoff<ConnectionEvent>>,
+    /// Replies to send back on an open connection. Replies to a closed
+    /// connection are dropped.
+    pub replies: SendPort<VecHandoff<(ConnectionId, Out)>>,
+}
+
 // These methods can't be wrapped up in a trait because async methods are not
 // allowed in traits (yet).
 
@@ -23,58 +64,113 @@ impl Hydroflow {
     where
         T: 'static + DeserializeOwned + Send,
     {
-        self.inbound_tcp_vertex_internal(Some(port)).await.1
+        self.inbound_tcp_vertex_internal(Some(port), future::ok, decode_with(BincodeCodec))
+            .await
+            .1
     }
 
     pub async fn inbound_tcp_vertex<T>(&mut self) -> (u16, RecvPort<VecHandoff<T>>)
     where
         T: 'static + DeserializeOwned + Send,
     {
-        self.inbound_tcp_vertex_internal(None).await
+        self.inbound_tcp_vertex_internal(None, future::ok, decode_with(BincodeCodec))
+            .await
+    }
+
+    /// Like [Self::inbound_tcp_vertex_port], but decodes messages with `codec`.
+    pub async fn inbound_tcp_vertex_port_with_codec<T, C>(
+        &mut self,
+        port: u16,
+        codec: C,
+    ) -> RecvPort<VecHandoff<T>>
+    where
+        T: 'static + Send,
+        C: Decode<T>,
+    {
+        self.inbound_tcp_vertex_internal(Some(port), future::ok, decode_with(codec))
+            .await
+            .1
     }
+
+    /// Like [Self::inbound_tcp_vertex], but decodes messages with `codec`.
+    pub async fn inbound_tcp_vertex_with_codec<T, C>(
+        &mut self,
+        codec: C,
+    ) -> (u16, RecvPort<VecHandoff<T>>)
+    where
+        T: 'static + Send,
+        C: Decode<T>,
+    {
+        self.inbound_tcp_vertex_internal(None, future::ok, decode_with(codec))
+            .await
+    }
+
     // TODO(justin): this needs to return a result/get rid of all the unwraps, I
     // guess we need a HydroflowError?
     /// Begins listening on some TCP port. Returns an [OutputPort] representing
-    /// the stream of messages received. Currently there is no notion of
-    /// identity to the connections received, if they are to be attached to some
+    /// the stream of messages received. There is no notion of identity to the
+    /// connections received here, if they are to be attached to some
     /// participant in the system, that needs to be included in the message
-    /// directly.
+    /// directly, or use [Self::inbound_tcp_connections] instead.
     ///
-    /// The messages will be interpreted to be bincode-encoded, length-delimited
-    /// messages, as produced by [Self::outbound_tcp_vertex].
-    async fn inbound_tcp_vertex_internal<T>(
+    /// Each accepted connection is first set up by `accept`, for example to
+    /// perform a TLS handshake; connections for which it fails are closed.
+    /// The messages will be interpreted as length-delimited frames, each
+    /// turned into zero or more messages by `decode`.
+    pub(super) async fn inbound_tcp_vertex_internal<T, A, Fut, S, F, I>(
         &mut self,
         port: Option<u16>,
+        accept: A,
+        decode: F,
     ) -> (u16, RecvPort<VecHandoff<T>>)
     where
-        T: 'static + DeserializeOwned + Send,
+        T: 'static + Send,
+        A: 'static + Send + Fn(TcpStream) -> Fut,
+        Fut: 'static + Send + Future<Output = std::io::Result<S>>,
+        S: 'static + Send + AsyncRead + AsyncWrite,
+        F: 'static + Clone + Send + Sync + Fn(Bytes) -> I,
+        I: Send + IntoIterator<Item = T>,
+        I::IntoIter: Send,
     {
         let listener = TcpListener::bind(format!("localhost:{}", port.unwrap_or(0)))
             .await
             .unwrap();
         let port = listener.local_addr().unwrap().port();
 
-        // TODO(justin): figure out an appropriate buffer here.
-        let (incoming_send, incoming_messages) = futures::channel::mpsc::channel(1024);
+        let (incoming_send, incoming_messages) = futures::channel::mpsc::channel(INGRESS_BUFFER);
 
         // Listen to incoming connections and spawn a tokio task for each one,
-        // which feeds into the channel.
+        // which feeds into the channel. The write half is shared with the
+        // connection manager, so outbound vertices can send back on it.
         // TODO(justin): give some way to get a handle into this thing.
+        let connection_manager = self.connection_manager().downgrade();
         tokio::spawn(async move {
             loop {
-                let (socket, _) = listener.accept().await.unwrap();
-                let (reader, _) = socket.into_split();
-                let mut reader = FramedRead::new(reader, LengthDelimitedCodec::new());
+                let (socket, addr) = listener.accept().await.unwrap();
+                let stream = accept(socket);
                 let mut incoming_send = incoming_send.clone();
+                let connection_manager = connection_manager.clone();
+                let decode = decode.clone();
                 tokio::spawn(async move {
-                    while let Some(msg) = reader.next().await {
-                        // TODO(justin): figure out error handling here.
-                        let msg = msg.unwrap();
-                        let out = bincode::deserialize(&msg).unwrap();
-                        incoming_send.send(out).await.unwrap();
+                    let stream = match stream.await {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            eprintln!("couldn't accept connection from {}: {}", addr, e);
+                            return;
+                        }
+                    };
+                    let (reader, writer) = tokio::io::split(stream);
+                    let registration = connection_manager
+                        .upgrade()
+                        .map(|manager| manager.register_inbound(addr, spawn_writer(writer)));
+                    let mut reader = FramedRead::new(reader, LengthDelimitedCodec::new());
+                    // TODO(justin): figure out error handling here.
+                    while let Some(Ok(frame)) = reader.next().await {
+                        for out in decode(frame.freeze()) {
+                            incoming_send.send(out).await.unwrap();
+                        }
                     }
-                    // TODO(justin): The connection is closed, so we should
-                    // clean up its metadata.
+                    drop(registration);
                 });
             }
         });
@@ -85,143 +181,189 @@ impl Hydroflow {
         (port, recv_port)
     }
 
-    pub async fn outbound_tcp_vertex<T>(&mut self) -> SendPort<VecHandoff<(Address, T)>>
+    /// Begins listening on some TCP port (or any free port if `port` is
+    /// `None`), keeping track of which connection each message arrived on.
+    /// Replies sent to [InboundTcpConnections::replies] are written back on
+    /// the same connection. Messages in both directions are length-delimited
+    /// frames encoded with `codec`.
+    pub async fn inbound_tcp_connections<In, Out, C>(
+        &mut self,
+        port: Option<u16>,
+        codec: C,
+    ) -> InboundTcpConnections<In, Out>
     where
-        T: 'static + Serialize + Send,
+        In: 'static + Send,
+        Out: 'static,
+        C: Decode<In> + Encode<Out>,
     {
-        let (mut connection_reqs_send, mut connection_reqs_recv) =
-            futures::channel::mpsc::channel(1024);
-        let (mut connections_send, mut connections_recv) = futures::channel::mpsc::channel(1024);
+        let listener = TcpListener::bind(format!("localhost:{}", port.unwrap_or(0)))
+            .await
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
 
-        // TODO(justin): handle errors here.
-        // Spawn an actor which establishes connections.
-        tokio::spawn(async move {
-            while let Some(addr) = connection_reqs_recv.next().await {
-                let addr: Address = addr;
-                connections_send
-                    .send((addr.clone(), TcpStream::connect(addr.clone()).await))
-                    .await
-                    .unwrap();
-            }
-        });
+        // Senders for the writer task of each open connection.
+        let writers = Arc::new(Mutex::new(
+            HashMap::<ConnectionId, UnboundedSender<Bytes>>::new(),
+        ));
 
-        enum ConnStatus<T> {
-            Pending(Vec<T>),
-            Connected(FramedWrite<TcpStream, LengthDelimitedCodec>),
-        }
+        let (incoming_send, incoming_messages) = futures::channel::mpsc::channel(INGRESS_BUFFER);
+        let (events_send, events_recv) = futures::channel::mpsc::unbounded();
 
-        let (mut outbound_messages_send, mut outbound_messages_recv) =
-            futures::channel::mpsc::channel(1024);
+        let accept_writers = writers.clone();
+        let decode = decode_with(codec.clone());
+        let connection_manager = self.connection_manager().downgrade();
         tokio::spawn(async move {
-            // TODO(justin): this cache should be global to the entire Hydroflow
-            // instance so we can reuse connections from inbound connections.
-            let mut connections = HashMap::<Address, ConnStatus<T>>::new();
+            for id in (0..).map(ConnectionId) {
+                let (socket, addr) = listener.accept().await.unwrap();
+                let (reader, writer) = socket.into_split();
 
-            loop {
-                tokio::select! {
-                    Some((addr, msg)) = outbound_messages_recv.next() => {
-                        let addr: Address = addr;
-                        let msg: T = msg;
-                        match connections.get_mut(&addr) {
-                            None => {
-                                // We have not seen this address before, open a
-                                // connection to it and buffer the message to be
-                                // sent once it's open.
-
-                                // TODO(justin): what do we do if the buffer is full here?
-                                connection_reqs_send.try_send(addr.clone()).unwrap();
-                                connections.insert(addr, ConnStatus::Pending(vec![msg]));
-                            }
-                            Some(ConnStatus::Pending(msgs)) => {
-                                // We have seen this address before but we're
-                                // still trying to connect to it, so buffer this
-                                // message so that when we _do_ connect we will
-                                // send it.
-                                msgs.push(msg);
-                            }
-                            Some(ConnStatus::Connected(conn)) => {
-                                // TODO(justin): move the actual sending here
-                                // into a different task so we don't have to
-                                // wait for the send.
-                                let msg = bincode::serialize(&msg).unwrap();
-                                conn.send(msg.into()).await.unwrap();
-                            }
-                        }
-                    },
-
-                    Some((addr, conn)) = connections_recv.next() => {
-                        match conn {
-                            Ok(conn) => {
-                                match connections.get_mut(&addr) {
-                                    Some(ConnStatus::Pending(msgs)) => {
-                                        let mut conn = FramedWrite::new(conn, LengthDelimitedCodec::new());
-                                        for msg in msgs.drain(..) {
-                                            // TODO(justin): move the actual sending here
-                                            // into a different task so we don't have to
-                                            // wait for the send.
-                                            let msg = bincode::serialize(&msg).unwrap();
-                                            conn.send(msg.into()).await.unwrap();
-                                        }
-                                        connections.insert(addr, ConnStatus::Connected(conn));
-                                    }
-                                    None => {
-                                        // This means nobody ever requested this
-                                        // connection, so we shouldn't have initiated it
-                                        // in the first place.
-                                        unreachable!()
-                                    }
-                                    Some(ConnStatus::Connected(_tcp)) => {
-                                        // This means we were already connected, so we
-                                        // shouldn't have connected again. If the
-                                        // connection cache becomes shared this could
-                                        // become reachable.
-                                        unreachable!()
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                // We couldn't connect to the address for some
-                                // reason.
-                                // TODO(justin): once we have a clearer picture
-                                // of error handling, we could do something like
-                                // send this error along a pipe to be handled by
-                                // someone else. For now, just log it and drop
-                                // any pending messages.
-                                eprintln!("couldn't connect to {}: {}", addr, e);
-                                connections.remove(&addr);
+                let reply_send = spawn_writer(writer);
+                let registration = connection_manager
+                    .upgrade()
+                    .map(|manager| manager.register_inbound(addr, reply_send.clone()));
+                accept_writers.lock().unwrap().insert(id, reply_send);
+
+                let _ = events_send.unbounded_send(ConnectionEvent::Connected(id, addr));
+
+                let mut reader = FramedRead::new(reader, LengthDelimitedCodec::new());
+                let mut incoming_send = incoming_send.clone();
+                let events_send = events_send.clone();
+                let writers = accept_writers.clone();
+                let decode = decode.clone();
+                tokio::spawn(async move {
+                    while let Some(Ok(msg)) = reader.next().await {
+                        if let Some(out) = decode(msg.freeze()) {
+                            if incoming_send.send((id, out)).await.is_err() {
+                                break;
                             }
                         }
-                    },
-                    else => break,
-                }
+                    }
+                    // The connection is closed, so clean up its writer.
+                    writers.lock().unwrap().remove(&id);
+                    drop(registration);
+                    let _ = events_send.unbounded_send(ConnectionEvent::Disconnected(id));
+                });
             }
         });
 
-        let mut buffered_messages = Vec::new();
-        let mut next_messages = Vec::new();
-        let (input_port, output_port) = self.make_edge("tcp egress handoff");
-        self.add_subgraph_sink("tcp egress stream", output_port, move |_ctx, recv| {
-            buffered_messages.extend(recv.take_inner());
-            for msg in buffered_messages.drain(..) {
-                if let Err(e) = outbound_messages_send.try_send(msg) {
-                    // If we weren't able to send a message (say, because the
-                    // buffer is full), we get handed it back in the error. If
-                    // this happens we hang onto the message to try sending it
-                    // again next time.
-                    next_messages.push(e.into_inner());
+        let (messages_send, messages) = self.make_edge("tcp connections ingress handoff");
+        self.add_input_from_stream(
+            "tcp connections ingress stream",
+            messages_send,
+            incoming_messages.map(Some),
+        );
+
+        let (events_send, events) = self.make_edge("tcp connections events handoff");
+        self.add_input_from_stream(
+            "tcp connections events stream",
+            events_send,
+            events_recv.map(Some),
+        );
+
+        let (replies, replies_recv) = self.make_edge("tcp connections egress handoff");
+        self.add_subgraph_sink("tcp connections egress", replies_recv, move |_ctx, recv| {
+            let writers = writers.lock().unwrap();
+            for (id, msg) in recv.take_inner() {
+                let msg = match codec.encode(&msg) {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        eprintln!("couldn't encode message: {}", e);
+                        continue;
+                    }
+                };
+                match writers.get(&id) {
+                    Some(writer) => {
+                        let _ = writer.unbounded_send(msg);
+                    }
+                    None => eprintln!("connection {:?} is closed, dropping reply", id),
                 }
             }
+        });
 
-            // NB. we don't need to flush the channel here due to the use of
-            // `try_send`.  It's guaranteed that there was space for the
-            // messages and that they were sent.
+        InboundTcpConnections {
+            port,
+            messages,
+            events,
+            replies,
+        }
+    }
 
-            // TODO(justin): we do need to make sure we get rescheduled if
-            // next_messages is empty here.
+    pub async fn outbound_tcp_vertex<T>(&mut self) -> SendPort<VecHandoff<(Address, T)>>
+    where
+        T: 'static + Serialize + Send,
+    {
+        self.outbound_tcp_vertex_with_codec(BincodeCodec).await
+    }
 
-            std::mem::swap(&mut buffered_messages, &mut next_messages);
+    /// Like [Self::outbound_tcp_vertex], but encodes messages with `codec`.
+    /// Messages which fail to encode are dropped.
+    ///
+    /// Messages are sent through this instance's
+    /// [connection manager](Self::connection_manager), which reuses open
+    /// connections (including inbound ones) and reconnects with backoff.
+    pub async fn outbound_tcp_vertex_with_codec<T, C>(
+        &mut self,
+        codec: C,
+    ) -> SendPort<VecHandoff<(Address, T)>>
+    where
+        T: 'static + Send,
+        C: Encode<T>,
+    {
+        let connection_manager = self.connection_manager().clone();
+        self.outbound_vertex_internal(connection_manager, codec)
+    }
+
+    /// Adds a sink which encodes messages with `codec` and sends them through
+    /// `connection_manager`.
+    pub(super) fn outbound_vertex_internal<T, C>(
+        &mut self,
+        connection_manager: ConnectionManager,
+        codec: C,
+    ) -> SendPort<VecHandoff<(Address, T)>>
+    where
+        T: 'static + Send,
+        C: Encode<T>,
+    {
+        let (input_port, output_port) = self.make_edge("tcp egress handoff");
+        self.add_subgraph_sink("tcp egress stream", output_port, move |_ctx, recv| {
+            for (addr, msg) in recv.take_inner() {
+                let addr: Address = addr;
+                match codec.encode(&msg) {
+                    Ok(msg) => connection_manager.send(addr, msg),
+                    Err(e) => eprintln!("couldn't encode message: {}", e),
+                }
+            }
         });
 
         input_port
     }
 }
+
+/// Decodes each frame as a single message with `codec`, dropping frames
+/// which fail to decode.
+pub(super) fn decode_with<T, C>(
+    codec: C,
+) -> impl 'static + Clone + Send + Sync + Fn(Bytes) -> Option<T>
+where
+    C: Decode<T>,
+{
+    move |frame| match codec.decode(frame) {
+        Ok(msg) => Some(msg),
+        Err(e) => {
+            eprintln!("couldn't decode message: {}", e);
+            None
+        }
+    }
+}
+
+/// Spawns a task which writes frames sent to the returned channel to `writer`,
+/// until the connection or the channel is closed.
+pub(super) fn spawn_writer<W>(writer: W) -> UnboundedSender<Bytes>
+where
+    W: 'static + AsyncWrite + Send,
+{
+    let (send, recv) = futures::channel::mpsc::unbounded();
+    let writer = FramedWrite::new(writer, LengthDelimitedCodec::new());
+    tokio::spawn(recv.map(Ok).forward(writer));
+    send
+}
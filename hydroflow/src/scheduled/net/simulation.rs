@@ -0,0 +1,341 @@
+//! A deterministic simulated network for testing distributed flows under
+//! adversarial delivery.
+//!
+//! Like the [loopback network](super::loopback), a [`SimulatedNetwork`] carries
+//! encoded messages between [`Hydroflow`] instances in the same process, but
+//! every message passes through a queue where it may be delayed (and thereby
+//! reordered), duplicated, or dropped, and links may be partitioned. All
+//! randomness comes from a single seeded RNG, and a [`Simulation`] steps every
+//! instance from one thread, so a run is fully determined by its seed.
+//!
+//! Time is measured in steps of the [`Simulation`]. A message sent during step
+//! `t` is delivered at the start of step `t + 1 + delay`.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures::StreamExt;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::codec::{BincodeCodec, Encode};
+use super::network_vertex::{decode_with, Address};
+use crate::scheduled::{
+    graph::Hydroflow,
+    graph_ext::GraphExt,
+    handoff::VecHandoff,
+    port::{RecvPort, SendPort},
+};
+
+/// The faults a [`SimulatedNetwork`] injects into each message.
+#[derive(Clone, Debug)]
+pub struct FaultConfig {
+    /// Each copy of a message is delayed by a uniformly random number of
+    /// steps in `min_delay..=max_delay`. Distinct delays reorder messages.
+    pub min_delay: u64,
+    pub max_delay: u64,
+    /// Probability that a message is dropped.
+    pub drop_probability: f64,
+    /// Probability that a message which isn't dropped is delivered twice.
+    pub duplicate_probability: f64,
+}
+
+impl Default for FaultConfig {
+    /// A reliable network, delivering every message in the next step.
+    fn default() -> Self {
+        Self {
+            min_delay: 0,
+            max_delay: 0,
+            drop_probability: 0.0,
+            duplicate_probability: 0.0,
+        }
+    }
+}
+
+impl FaultConfig {
+    fn assert_valid(&self) {
+        assert!(self.min_delay <= self.max_delay);
+        assert!(
+            (0.0..=1.0).contains(&self.drop_probability),
+            "drop_probability must be in 0.0..=1.0."
+        );
+        assert!(
+            (0.0..=1.0).contains(&self.duplicate_probability),
+            "duplicate_probability must be in 0.0..=1.0."
+        );
+    }
+}
+
+/// Counts of what happened to the messages sent so far.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NetworkStats {
+    pub sent: u64,
+    pub delivered: u64,
+    pub dropped: u64,
+    pub duplicated: u64,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct InFlight {
+    deliver_at: u64,
+    /// Breaks ties between messages delivered in the same step.
+    seq: u64,
+    from: Address,
+    to: Address,
+    frame: Bytes,
+}
+
+struct SimulatedInner {
+    rng: StdRng,
+    config: FaultConfig,
+    now: u64,
+    next_seq: u64,
+    endpoints: HashMap<Address, UnboundedSender<Bytes>>,
+    in_flight: BinaryHeap<Reverse<InFlight>>,
+    /// Partition group of each partitioned address.
+    groups: HashMap<Address, usize>,
+    stats: NetworkStats,
+}
+
+impl SimulatedInner {
+    fn can_reach(&self, from: &str, to: &str) -> bool {
+        match (self.groups.get(from), self.groups.get(to)) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        }
+    }
+
+    fn enqueue(&mut self, from: Address, to: Address, frame: Bytes) {
+        let delay = self
+            .rng
+            .gen_range(self.config.min_delay..=self.config.max_delay);
+        self.in_flight.push(Reverse(InFlight {
+            deliver_at: self.now + 1 + delay,
+            seq: self.next_seq,
+            from,
+            to,
+            frame,
+        }));
+        self.next_seq += 1;
+    }
+}
+
+/// A shared simulated network. Cheap to clone.
+#[derive(Clone)]
+pub struct SimulatedNetwork {
+    inner: Arc<Mutex<SimulatedInner>>,
+}
+
+impl SimulatedNetwork {
+    /// Creates a network injecting faults according to `config`, with all
+    /// random choices made by an RNG seeded with `seed`. Panics if `config`
+    /// has `max_delay < min_delay`, or a probability outside `0.0..=1.0`.
+    pub fn new(seed: u64, config: FaultConfig) -> Self {
+        config.assert_valid();
+        Self {
+            inner: Arc::new(Mutex::new(SimulatedInner {
+                rng: StdRng::seed_from_u64(seed),
+                config,
+                now: 0,
+                next_seq: 0,
+                endpoints: HashMap::new(),
+                in_flight: BinaryHeap::new(),
+                groups: HashMap::new(),
+                stats: NetworkStats::default(),
+            })),
+        }
+    }
+
+    /// Replaces the fault configuration for messages sent from now on. Panics
+    /// if `config` is invalid, as for [`Self::new`].
+    pub fn set_faults(&self, config: FaultConfig) {
+        config.assert_valid();
+        self.inner.lock().unwrap().config = config;
+    }
+
+    /// Partitions the network: messages between addresses in different
+    /// groups are dropped, including those already in flight. Addresses not
+    /// in any group can still reach everyone. Replaces any earlier partition.
+    pub fn partition<G>(&self, groups: impl IntoIterator<Item = G>)
+    where
+        G: IntoIterator<Item = Address>,
+    {
+        let mut inner = self.inner.lock().unwrap();
+        inner.groups = groups
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, group)| group.into_iter().map(move |address| (address, i)))
+            .collect();
+    }
+
+    /// Removes any partition.
+    pub fn heal(&self) {
+        self.inner.lock().unwrap().groups.clear();
+    }
+
+    /// The current step.
+    pub fn now(&self) -> u64 {
+        self.inner.lock().unwrap().now
+    }
+
+    /// The number of messages sent but not yet delivered or dropped.
+    pub fn in_flight(&self) -> usize {
+        self.inner.lock().unwrap().in_flight.len()
+    }
+
+    pub fn stats(&self) -> NetworkStats {
+        self.inner.lock().unwrap().stats.clone()
+    }
+
+    /// Binds `address`. Panics if it is already bound.
+    fn bind(&self, address: Address) -> UnboundedReceiver<Bytes> {
+        let mut inner = self.inner.lock().unwrap();
+        let (send, recv) = futures::channel::mpsc::unbounded();
+        assert!(
+            inner.endpoints.insert(address.clone(), send).is_none(),
+            "Simulated address {} is already bound.",
+            address
+        );
+        recv
+    }
+
+    /// Decides the fate of `frame`, queueing zero, one or two copies of it.
+    fn send(&self, from: Address, to: Address, frame: Bytes) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.stats.sent += 1;
+        let drop_probability = inner.config.drop_probability;
+        if !inner.can_reach(&from, &to) || inner.rng.gen_bool(drop_probability) {
+            inner.stats.dropped += 1;
+            return;
+        }
+        let duplicate_probability = inner.config.duplicate_probability;
+        if inner.rng.gen_bool(duplicate_probability) {
+            inner.stats.duplicated += 1;
+            inner.enqueue(from.clone(), to.clone(), frame.clone());
+        }
+        inner.enqueue(from, to, frame);
+    }
+
+    /// Advances to the next step and delivers the messages due by then.
+    fn advance(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.now += 1;
+        while let Some(Reverse(msg)) = inner.in_flight.peek() {
+            if inner.now < msg.deliver_at {
+                break;
+            }
+            let Reverse(msg) = inner.in_flight.pop().unwrap();
+            let delivered = inner.can_reach(&msg.from, &msg.to)
+                && inner
+                    .endpoints
+                    .get(&msg.to)
+                    .map_or(false, |endpoint| endpoint.unbounded_send(msg.frame).is_ok());
+            if delivered {
+                inner.stats.delivered += 1;
+            } else {
+                inner.stats.dropped += 1;
+            }
+        }
+    }
+}
+
+/// Runs several [`Hydroflow`] instances connected by a [`SimulatedNetwork`],
+/// all in the current thread.
+pub struct Simulation {
+    network: SimulatedNetwork,
+    instances: Vec<Hydroflow>,
+}
+
+impl Simulation {
+    /// Creates a simulation over a new [`SimulatedNetwork`].
+    pub fn new(seed: u64, config: FaultConfig) -> Self {
+        Self {
+            network: SimulatedNetwork::new(seed, config),
+            instances: Vec::new(),
+        }
+    }
+
+    pub fn network(&self) -> &SimulatedNetwork {
+        &self.network
+    }
+
+    /// Adds an instance to be stepped, returning its index.
+    pub fn add_instance(&mut self, hydroflow: Hydroflow) -> usize {
+        self.instances.push(hydroflow);
+        self.instances.len() - 1
+    }
+
+    pub fn instance_mut(&mut self, idx: usize) -> &mut Hydroflow {
+        &mut self.instances[idx]
+    }
+
+    /// Delivers the messages due at the next step, then ticks every instance
+    /// in the order they were added.
+    pub fn step(&mut self) {
+        self.network.advance();
+        for instance in self.instances.iter_mut() {
+            instance.tick();
+        }
+    }
+
+    /// Steps until no messages are in flight, or `max_steps` have passed.
+    /// Returns whether the network went quiet.
+    pub fn run_until_quiescent(&mut self, max_steps: u64) -> bool {
+        for _ in 0..max_steps {
+            self.step();
+            if self.network.in_flight() == 0 {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Hydroflow {
+    /// Like [Self::inbound_loopback_vertex_at], but receives from a
+    /// [`SimulatedNetwork`].
+    pub fn inbound_simulated_vertex<T>(
+        &mut self,
+        network: &SimulatedNetwork,
+        address: Address,
+    ) -> RecvPort<VecHandoff<T>>
+    where
+        T: 'static + DeserializeOwned,
+    {
+        let messages = network.bind(address).map(decode_with(BincodeCodec));
+
+        let (send_port, recv_port) = self.make_edge("simulated ingress handoff");
+        self.add_input_from_stream("simulated ingress", send_port, messages);
+        recv_port
+    }
+
+    /// Like [Self::outbound_loopback_vertex], but sends over a
+    /// [`SimulatedNetwork`]. `from` is the sender's own address, which
+    /// decides which side of a partition it is on.
+    pub fn outbound_simulated_vertex<T>(
+        &mut self,
+        network: &SimulatedNetwork,
+        from: Address,
+    ) -> SendPort<VecHandoff<(Address, T)>>
+    where
+        T: 'static + Serialize,
+    {
+        let network = network.clone();
+        let codec = BincodeCodec;
+        let (input_port, output_port) = self.make_edge("simulated egress handoff");
+        self.add_subgraph_sink("simulated egress", output_port, move |_ctx, recv| {
+            for (addr, msg) in recv.take_inner() {
+                match Encode::<T>::encode(&codec, &msg) {
+                    Ok(msg) => network.send(from.clone(), addr, msg),
+                    Err(e) => eprintln!("couldn't encode message: {}", e),
+                }
+            }
+        });
+
+        input_port
+    }
+}
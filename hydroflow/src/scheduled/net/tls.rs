@@ -0,0 +1,179 @@
+//! TLS for the TCP network vertices, using [rustls](https://docs.rs/rustls).
+//! Only available with the `tls` feature.
+//!
+//! Servers need a certificate chain and private key, and clients the root
+//! certificates to trust; [`load_certs`] and [`load_private_key`] read these
+//! from PEM files. The framing inside the TLS session is the same as for the
+//! plaintext vertices.
+
+use std::fs::File;
+use std::io::{BufReader, Error, ErrorKind};
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{
+    Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig, ServerName,
+};
+use tokio_rustls::{client, TlsAcceptor, TlsConnector};
+
+use super::codec::{BincodeCodec, Decode, Encode};
+use super::connection_manager::ConnectionManager;
+use super::network_vertex::{decode_with, Address};
+use super::Message;
+use crate::scheduled::{
+    graph::Hydroflow,
+    handoff::VecHandoff,
+    port::{RecvPort, SendPort},
+};
+
+pub use tokio_rustls::rustls;
+
+/// Reads all the certificates in the PEM file at `path`.
+pub fn load_certs(path: impl AsRef<Path>) -> std::io::Result<Vec<Certificate>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let certs = rustls_pemfile::certs(&mut reader)?;
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+/// Reads the first PKCS #8 or RSA private key in the PEM file at `path`.
+pub fn load_private_key(path: impl AsRef<Path>) -> std::io::Result<PrivateKey> {
+    let mut reader = BufReader::new(File::open(path)?);
+    while let Some(item) = rustls_pemfile::read_one(&mut reader)? {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key) | rustls_pemfile::Item::RSAKey(key) => {
+                return Ok(PrivateKey(key))
+            }
+            _ => {}
+        }
+    }
+    Err(Error::new(ErrorKind::InvalidData, "no private key found"))
+}
+
+/// A server config presenting the certificate chain `certs` for `key`, with
+/// no client authentication.
+pub fn server_config(
+    certs: Vec<Certificate>,
+    key: PrivateKey,
+) -> std::io::Result<Arc<ServerConfig>> {
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+    Ok(Arc::new(config))
+}
+
+/// A client config trusting servers whose certificates chain to `roots`.
+pub fn client_config(roots: &[Certificate]) -> std::io::Result<Arc<ClientConfig>> {
+    let mut root_store = RootCertStore::empty();
+    for root in roots {
+        root_store
+            .add(root)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+    }
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_store)
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+/// Starts a TLS session on `stream`, which is connected to `address`.
+pub(super) async fn connect(
+    connector: &TlsConnector,
+    address: &str,
+    stream: TcpStream,
+) -> std::io::Result<client::TlsStream<TcpStream>> {
+    let host = address
+        .rsplit_once(':')
+        .map_or(address, |(host, _port)| host);
+    let server_name = ServerName::try_from(host.trim_start_matches('[').trim_end_matches(']'))
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+    connector.connect(server_name, stream).await
+}
+
+impl Hydroflow {
+    /// Like [Self::add_tcp_stream], but for a stream with an established TLS
+    /// session, such as one returned by [TlsConnector::connect] or
+    /// [TlsAcceptor::accept].
+    pub fn add_tls_stream<S>(
+        &mut self,
+        stream: S,
+    ) -> (SendPort<VecHandoff<Message>>, RecvPort<VecHandoff<Message>>)
+    where
+        S: 'static + AsyncRead + AsyncWrite,
+    {
+        let (reader, writer) = tokio::io::split(stream);
+
+        (
+            self.register_write_tcp_stream(writer),
+            self.register_read_tcp_stream(reader),
+        )
+    }
+
+    /// Like [Self::inbound_tcp_vertex], but accepts TLS connections using
+    /// `config`. Listens on `port`, or any free port if `None`.
+    pub async fn inbound_tls_vertex<T>(
+        &mut self,
+        port: Option<u16>,
+        config: Arc<ServerConfig>,
+    ) -> (u16, RecvPort<VecHandoff<T>>)
+    where
+        T: 'static + DeserializeOwned + Send,
+    {
+        self.inbound_tls_vertex_with_codec(port, config, BincodeCodec)
+            .await
+    }
+
+    /// Like [Self::inbound_tls_vertex], but decodes messages with `codec`.
+    /// Connections which fail the TLS handshake are closed.
+    pub async fn inbound_tls_vertex_with_codec<T, C>(
+        &mut self,
+        port: Option<u16>,
+        config: Arc<ServerConfig>,
+        codec: C,
+    ) -> (u16, RecvPort<VecHandoff<T>>)
+    where
+        T: 'static + Send,
+        C: Decode<T>,
+    {
+        let acceptor = TlsAcceptor::from(config);
+        self.inbound_tcp_vertex_internal(
+            port,
+            move |socket| acceptor.accept(socket),
+            decode_with(codec),
+        )
+        .await
+    }
+
+    /// Like [Self::outbound_tcp_vertex], but connects with TLS using
+    /// `config`. These connections are kept separately from this instance's
+    /// [connection manager](Self::connection_manager), so are never shared
+    /// with plaintext vertices.
+    pub async fn outbound_tls_vertex<T>(
+        &mut self,
+        config: Arc<ClientConfig>,
+    ) -> SendPort<VecHandoff<(Address, T)>>
+    where
+        T: 'static + Serialize + Send,
+    {
+        self.outbound_tls_vertex_with_codec(config, BincodeCodec)
+            .await
+    }
+
+    /// Like [Self::outbound_tls_vertex], but encodes messages with `codec`.
+    pub async fn outbound_tls_vertex_with_codec<T, C>(
+        &mut self,
+        config: Arc<ClientConfig>,
+        codec: C,
+    ) -> SendPort<VecHandoff<(Address, T)>>
+    where
+        T: 'static + Send,
+        C: Encode<T>,
+    {
+        self.outbound_vertex_internal(ConnectionManager::with_tls(config), codec)
+    }
+}
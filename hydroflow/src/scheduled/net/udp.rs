@@ -0,0 +1,100 @@
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::task::Poll;
+
+use tokio::net::UdpSocket;
+
+use super::{Message, ADDRESS_LEN};
+use crate::scheduled::{
+    graph::Hydroflow,
+    graph_ext::GraphExt,
+    handoff::VecHandoff,
+    port::{RecvPort, SendPort},
+};
+
+/// Largest payload which fits in a single UDP datagram.
+const MAX_DATAGRAM_LEN: usize = 65_507;
+
+impl Hydroflow {
+    /// Adds a UDP socket. Each [Message] is sent as a single datagram to the
+    /// paired address, and each datagram received is decoded as a [Message]
+    /// tagged with its sender's address.
+    ///
+    /// UDP makes no delivery guarantees: messages may be lost, duplicated, or
+    /// reordered. Messages too large for one datagram are dropped.
+    #[allow(clippy::type_complexity)]
+    pub fn add_udp_socket(
+        &mut self,
+        socket: UdpSocket,
+    ) -> (
+        SendPort<VecHandoff<(SocketAddr, Message)>>,
+        RecvPort<VecHandoff<(SocketAddr, Message)>>,
+    ) {
+        let socket = Arc::new(socket);
+        (
+            self.register_write_udp_socket(socket.clone()),
+            self.register_read_udp_socket(socket),
+        )
+    }
+
+    fn register_read_udp_socket(
+        &mut self,
+        socket: Arc<UdpSocket>,
+    ) -> RecvPort<VecHandoff<(SocketAddr, Message)>> {
+        let datagrams = futures::stream::unfold(socket, |socket| async move {
+            let mut buf = vec![0; MAX_DATAGRAM_LEN];
+            loop {
+                match socket.recv_from(&mut buf).await {
+                    Ok((len, addr)) if len < ADDRESS_LEN => {
+                        eprintln!("dropping truncated datagram from {}", addr);
+                    }
+                    Ok((len, addr)) => {
+                        buf.truncate(len);
+                        let message = Message::decode(buf.into());
+                        return Some((Some((addr, message)), socket));
+                    }
+                    // E.g. ICMP port unreachable from an earlier send.
+                    Err(e) => eprintln!("udp recv failed: {}", e),
+                }
+            }
+        });
+
+        let (send_port, recv_port) = self.make_edge("udp ingress handoff");
+        self.add_input_from_stream("udp ingress", send_port, Box::pin(datagrams));
+        recv_port
+    }
+
+    fn register_write_udp_socket(
+        &mut self,
+        socket: Arc<UdpSocket>,
+    ) -> SendPort<VecHandoff<(SocketAddr, Message)>> {
+        let mut message_queue = VecDeque::new();
+
+        let (input_port, output_port) =
+            self.make_edge::<_, VecHandoff<(SocketAddr, Message)>>("udp egress handoff");
+        self.add_subgraph_sink("udp egress", output_port, move |ctx, recv| {
+            let waker = ctx.waker();
+            let mut cx = std::task::Context::from_waker(&waker);
+
+            // TODO(mingwei): queue may grow unbounded? Subtle rate matching concern.
+            message_queue.extend(recv.take_inner().into_iter().map(|(addr, message)| {
+                let mut buf = Vec::new();
+                Message::encode(&message, &mut buf);
+                (addr, buf)
+            }));
+            while let Some((addr, buf)) = message_queue.front() {
+                match socket.poll_send_to(&mut cx, buf, *addr) {
+                    // The waker reschedules this subgraph once the socket is
+                    // writable again.
+                    Poll::Pending => break,
+                    Poll::Ready(Ok(_)) => {}
+                    Poll::Ready(Err(e)) => eprintln!("udp send to {} failed: {}", addr, e),
+                }
+                message_queue.pop_front();
+            }
+        });
+
+        input_port
+    }
+}
@@ -0,0 +1,143 @@
+//! Network vertices over Unix domain sockets, for talking to other processes
+//! on the same machine without binding TCP ports. Messages are
+//! length-delimited frames, exactly as with the TCP vertices.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures::{SinkExt, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::net::{UnixListener, UnixStream};
+use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
+
+use super::codec::{BincodeCodec, Decode, Encode};
+use super::network_vertex::{decode_with, INGRESS_BUFFER};
+use crate::scheduled::{
+    graph::Hydroflow,
+    graph_ext::GraphExt,
+    handoff::VecHandoff,
+    port::{RecvPort, SendPort},
+};
+
+impl Hydroflow {
+    /// Like [Self::inbound_tcp_vertex], but listens on a Unix domain socket
+    /// bound at `path`. Must be called from within a Tokio runtime.
+    pub fn inbound_unix_vertex<T>(&mut self, path: &Path) -> RecvPort<VecHandoff<T>>
+    where
+        T: 'static + DeserializeOwned + Send,
+    {
+        self.inbound_unix_vertex_with_codec(path, BincodeCodec)
+    }
+
+    /// Like [Self::inbound_unix_vertex], but decodes messages with `codec`.
+    pub fn inbound_unix_vertex_with_codec<T, C>(
+        &mut self,
+        path: &Path,
+        codec: C,
+    ) -> RecvPort<VecHandoff<T>>
+    where
+        T: 'static + Send,
+        C: Decode<T>,
+    {
+        let listener = UnixListener::bind(path).unwrap();
+        let decode = decode_with(codec);
+
+        let (incoming_send, incoming_messages) = futures::channel::mpsc::channel(INGRESS_BUFFER);
+
+        tokio::spawn(async move {
+            loop {
+                let (socket, _) = listener.accept().await.unwrap();
+                let mut reader = FramedRead::new(socket, LengthDelimitedCodec::new());
+                let mut incoming_send = incoming_send.clone();
+                let decode = decode.clone();
+                tokio::spawn(async move {
+                    while let Some(Ok(msg)) = reader.next().await {
+                        if let Some(out) = decode(msg.freeze()) {
+                            incoming_send.send(out).await.unwrap();
+                        }
+                    }
+                });
+            }
+        });
+
+        let (send_port, recv_port) = self.make_edge("unix ingress handoff");
+        self.add_input_from_stream(
+            "unix ingress stream",
+            send_port,
+            incoming_messages.map(Some),
+        );
+        recv_port
+    }
+
+    /// Like [Self::outbound_tcp_vertex], but sends to Unix domain sockets,
+    /// keeping one connection open per path. Messages to a path which can't
+    /// be connected to are dropped.
+    pub fn outbound_unix_vertex<T>(&mut self) -> SendPort<VecHandoff<(PathBuf, T)>>
+    where
+        T: 'static + Serialize + Send,
+    {
+        self.outbound_unix_vertex_with_codec(BincodeCodec)
+    }
+
+    /// Like [Self::outbound_unix_vertex], but encodes messages with `codec`.
+    pub fn outbound_unix_vertex_with_codec<T, C>(
+        &mut self,
+        codec: C,
+    ) -> SendPort<VecHandoff<(PathBuf, T)>>
+    where
+        T: 'static + Send,
+        C: Encode<T>,
+    {
+        let mut connections = HashMap::<PathBuf, UnboundedSender<Bytes>>::new();
+        let (input_port, output_port) = self.make_edge("unix egress handoff");
+        self.add_subgraph_sink("unix egress stream", output_port, move |_ctx, recv| {
+            for (path, msg) in recv.take_inner() {
+                let path: PathBuf = path;
+                let msg = match codec.encode(&msg) {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        eprintln!("couldn't encode message: {}", e);
+                        continue;
+                    }
+                };
+                let msg = match connections.get(&path) {
+                    Some(sender) => match sender.unbounded_send(msg) {
+                        Ok(()) => continue,
+                        // The connection was closed, open a new one.
+                        Err(e) => e.into_inner(),
+                    },
+                    None => msg,
+                };
+                let (sender, receiver) = futures::channel::mpsc::unbounded();
+                sender.unbounded_send(msg).unwrap();
+                tokio::spawn(write_unix_stream(path.clone(), receiver));
+                connections.insert(path, sender);
+            }
+        });
+
+        input_port
+    }
+}
+
+/// Connects to `path` and writes frames from `receiver` to it, until the
+/// connection or the channel is closed.
+async fn write_unix_stream(path: PathBuf, mut receiver: UnboundedReceiver<Bytes>) {
+    let stream = match UnixStream::connect(&path).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            eprintln!("couldn't connect to {}: {}", path.display(), e);
+            receiver.close();
+            return;
+        }
+    };
+    let mut writer = FramedWrite::new(stream, LengthDelimitedCodec::new());
+    while let Some(frame) = receiver.next().await {
+        if let Err(e) = writer.send(frame).await {
+            eprintln!("couldn't write to {}: {}", path.display(), e);
+            receiver.close();
+            return;
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use std::{
+    cell::RefCell,
     collections::{HashMap, HashSet},
+    rc::Rc,
     sync::{mpsc::channel, Arc, Mutex},
     time::Duration,
 };
@@ -14,12 +16,32 @@ use hydroflow::{
         },
         HydroflowBuilder,
     },
-    scheduled::{graph_ext::GraphExt, handoff::VecHandoff, port::RecvPort},
+    lang::{
+        lattice::{
+            dom_pair::DomPairRepr, map_union::MapUnionRepr, ord::MaxRepr, LatticeRepr, Merge,
+        },
+        tag,
+    },
+    scheduled::{
+        graph_ext::GraphExt,
+        handoff::VecHandoff,
+        net::{
+            batching::{BatchConfig, Compression},
+            codec::{BincodeCodec, JsonCodec},
+            connection_manager::{Backoff, ConnectionHealth, ConnectionManager},
+            loopback::LoopbackNetwork,
+            mux::Channel,
+            network_vertex::ConnectionEvent,
+            simulation::{FaultConfig, NetworkStats, Simulation},
+            Message,
+        },
+        port::RecvPort,
+    },
 };
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use tokio::{
-    net::{TcpListener, TcpStream},
+    net::{TcpListener, TcpStream, UdpSocket},
     sync::mpsc::{Receiver, Sender},
 };
 use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
@@ -289,6 +311,1054 @@ fn test_echo_server_reconnect() {
     });
 }
 
+#[test]
+fn test_json_codec_interop() {
+    // A hand-written peer sends length-prefixed JSON to Hydroflow, which
+    // forwards each message back out to the peer's listener as JSON.
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async move {
+        let listener = TcpListener::bind("localhost:0").await.unwrap();
+        let peer_addr = format!("localhost:{}", listener.local_addr().unwrap().port());
+
+        let (port_send, port_recv) = channel();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            rt.block_on(async move {
+                let mut builder = HydroflowBuilder::default();
+
+                let (port, inbound) = builder
+                    .hydroflow
+                    .inbound_tcp_vertex_with_codec::<EchoResponse, _>(JsonCodec)
+                    .await;
+                port_send.send(port).unwrap();
+                let inbound = builder.wrap_input(inbound);
+
+                let outbound = builder
+                    .hydroflow
+                    .outbound_tcp_vertex_with_codec(JsonCodec)
+                    .await;
+                let outbound = builder.wrap_output(outbound);
+
+                builder.add_subgraph(
+                    "forward",
+                    inbound
+                        .flatten()
+                        .map(move |response| Some((peer_addr.clone(), response)))
+                        .pull_to_push()
+                        .push_to(outbound),
+                );
+
+                builder.build().run_async().await.unwrap();
+            });
+        });
+        let port = port_recv.recv().unwrap();
+
+        let json: &[u8] = br#"{"payload":"hello"}"#;
+        let conn = TcpStream::connect(format!("localhost:{}", port))
+            .await
+            .unwrap();
+        let mut writer = FramedWrite::new(conn, LengthDelimitedCodec::new());
+        writer.send(json.into()).await.unwrap();
+
+        let (conn, _) = listener.accept().await.unwrap();
+        let mut reader = FramedRead::new(conn, LengthDelimitedCodec::new());
+        let frame = reader.next().await.unwrap().unwrap();
+        assert_eq!(json, &*frame);
+    });
+}
+
+#[test]
+fn test_inbound_tcp_connections() {
+    // The server replies to each message on the connection it arrived on, and
+    // reports connection events back to the test.
+    let (port_send, port_recv) = channel();
+    let (events_send, events_recv) = channel();
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let mut builder = HydroflowBuilder::default();
+
+            let conns = builder
+                .hydroflow
+                .inbound_tcp_connections::<String, String, _>(None, BincodeCodec)
+                .await;
+            port_send.send(conns.port).unwrap();
+
+            let messages = builder.wrap_input(conns.messages);
+            let events = builder.wrap_input(conns.events);
+            let replies = builder.wrap_output(conns.replies);
+
+            builder.add_subgraph(
+                "reply",
+                messages
+                    .flatten()
+                    .map(|(id, msg)| Some((id, msg.to_uppercase())))
+                    .pull_to_push()
+                    .push_to(replies),
+            );
+            builder.add_subgraph(
+                "events",
+                events
+                    .flatten()
+                    .pull_to_push()
+                    .for_each(move |event| events_send.send(event).unwrap()),
+            );
+
+            builder.build().run_async().await.unwrap();
+        });
+    });
+    let port = port_recv.recv().unwrap();
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async move {
+        let mut clients = Vec::new();
+        for _ in 0..2 {
+            let conn = TcpStream::connect(format!("localhost:{}", port))
+                .await
+                .unwrap();
+            let (reader, writer) = conn.into_split();
+            clients.push((
+                FramedRead::new(reader, LengthDelimitedCodec::new()),
+                FramedWrite::new(writer, LengthDelimitedCodec::new()),
+            ));
+        }
+
+        for (i, (_, writer)) in clients.iter_mut().enumerate() {
+            let msg = bincode::serialize(&format!("client {}", i)).unwrap();
+            writer.send(msg.into()).await.unwrap();
+        }
+        for (i, (reader, _)) in clients.iter_mut().enumerate() {
+            let reply = reader.next().await.unwrap().unwrap();
+            let reply: String = bincode::deserialize(&reply).unwrap();
+            assert_eq!(format!("CLIENT {}", i), reply);
+        }
+
+        // Close the first client's connection.
+        clients.remove(0);
+    });
+
+    let mut connected = Vec::new();
+    let disconnected = loop {
+        match events_recv.recv().unwrap() {
+            ConnectionEvent::Connected(id, _) => connected.push(id),
+            ConnectionEvent::Disconnected(id) => break id,
+        }
+    };
+    assert_eq!(2, connected.len());
+    assert!(connected.contains(&disconnected));
+}
+
+#[test]
+fn test_connection_manager_reconnect() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async move {
+        // Find a free port, then stop listening on it.
+        let listener = TcpListener::bind("localhost:0").await.unwrap();
+        let addr = format!("localhost:{}", listener.local_addr().unwrap().port());
+        drop(listener);
+
+        let manager = ConnectionManager::default();
+        manager.set_backoff(Backoff {
+            initial: Duration::from_millis(5),
+            max: Duration::from_millis(20),
+            max_attempts: None,
+        });
+        manager.send(addr.clone(), "hello".into());
+
+        // Nobody is listening, so the connection backs off but keeps the message.
+        while !matches!(
+            manager.health(&addr),
+            Some(ConnectionHealth::Backoff { .. })
+        ) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let listener = TcpListener::bind(&addr).await.unwrap();
+        let (conn, _) = listener.accept().await.unwrap();
+        let mut reader = FramedRead::new(conn, LengthDelimitedCodec::new());
+        assert_eq!(b"hello", &*reader.next().await.unwrap().unwrap());
+        assert_eq!(Some(ConnectionHealth::Connected), manager.health(&addr));
+    });
+}
+
+#[test]
+fn test_connection_manager_gives_up() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async move {
+        let listener = TcpListener::bind("localhost:0").await.unwrap();
+        let addr = format!("localhost:{}", listener.local_addr().unwrap().port());
+        drop(listener);
+
+        let manager = ConnectionManager::default();
+        manager.set_backoff(Backoff {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(1),
+            max_attempts: Some(3),
+        });
+        manager.send(addr.clone(), "dropped".into());
+
+        while !matches!(manager.health(&addr), Some(ConnectionHealth::Failed { .. })) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        // A later message starts a fresh connection.
+        let listener = TcpListener::bind(&addr).await.unwrap();
+        manager.send(addr.clone(), "delivered".into());
+        let (conn, _) = listener.accept().await.unwrap();
+        let mut reader = FramedRead::new(conn, LengthDelimitedCodec::new());
+        assert_eq!(b"delivered", &*reader.next().await.unwrap().unwrap());
+    });
+}
+
+#[test]
+fn test_connection_manager_drop_closes_connections() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async move {
+        let listener = TcpListener::bind("localhost:0").await.unwrap();
+        let addr = format!("localhost:{}", listener.local_addr().unwrap().port());
+
+        let manager = ConnectionManager::default();
+        manager.send(addr, "hello".into());
+        let (conn, _) = listener.accept().await.unwrap();
+        let mut reader = FramedRead::new(conn, LengthDelimitedCodec::new());
+        assert_eq!(b"hello", &*reader.next().await.unwrap().unwrap());
+
+        drop(manager);
+        assert!(reader.next().await.is_none());
+    });
+}
+
+#[test]
+fn test_connection_manager_closed_inbound() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async move {
+        let mut df = hydroflow::scheduled::graph::Hydroflow::new();
+        let (port, _inbound) = df.inbound_tcp_vertex::<String>().await;
+        let manager = df.connection_manager().clone();
+
+        let conn = TcpStream::connect(format!("localhost:{}", port))
+            .await
+            .unwrap();
+        let peer = conn.local_addr().unwrap().to_string();
+        while manager.health(&peer) != Some(ConnectionHealth::Connected) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        drop(conn);
+        while manager.health(&peer) != Some(ConnectionHealth::Closed) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        // The peer's port was ephemeral, so this is dropped rather than
+        // connecting to it.
+        manager.send(peer.clone(), "dropped".into());
+        assert_eq!(Some(ConnectionHealth::Closed), manager.health(&peer));
+    });
+}
+
+#[test]
+fn test_outbound_reuses_inbound_connection() {
+    // The server sends to the address of a peer which connected to it, which
+    // should go back over the peer's own connection.
+    let (port_send, port_recv) = channel();
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let mut builder = HydroflowBuilder::default();
+
+            let (port, inbound) = builder.hydroflow.inbound_tcp_vertex::<String>().await;
+            port_send.send(port).unwrap();
+            let inbound = builder.wrap_input(inbound);
+
+            let outbound = builder.hydroflow.outbound_tcp_vertex().await;
+            let outbound = builder.wrap_output(outbound);
+
+            builder.add_subgraph(
+                "greet",
+                inbound
+                    .flatten()
+                    .map(|peer: String| Some((peer, "welcome".to_owned())))
+                    .pull_to_push()
+                    .push_to(outbound),
+            );
+
+            builder.build().run_async().await.unwrap();
+        });
+    });
+    let port = port_recv.recv().unwrap();
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async move {
+        let conn = TcpStream::connect(format!("localhost:{}", port))
+            .await
+            .unwrap();
+        let local_addr = conn.local_addr().unwrap().to_string();
+        let (reader, writer) = conn.into_split();
+        let mut reader = FramedRead::new(reader, LengthDelimitedCodec::new());
+        let mut writer = FramedWrite::new(writer, LengthDelimitedCodec::new());
+
+        let msg = bincode::serialize(&local_addr).unwrap();
+        writer.send(msg.into()).await.unwrap();
+
+        let reply = reader.next().await.unwrap().unwrap();
+        let reply: String = bincode::deserialize(&reply).unwrap();
+        assert_eq!("welcome", reply);
+    });
+}
+
+#[test]
+fn test_udp_socket() {
+    // Socket A pings socket B, which echoes each datagram back to its sender.
+    let (out_send, out_recv) = channel();
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let mut builder = HydroflowBuilder::default();
+
+            let socket_a = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            let socket_b = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            let addr_b = socket_b.local_addr().unwrap();
+            out_send.send((addr_b, None)).unwrap();
+
+            let (a_send, a_recv) = builder.add_udp_socket(socket_a);
+            let (b_send, b_recv) = builder.add_udp_socket(socket_b);
+
+            builder.add_subgraph(
+                "ping",
+                IterPullSurface::new(0..3)
+                    .map(move |address| {
+                        let batch = format!("ping {}", address).into_bytes().into();
+                        Some((addr_b, Message { address, batch }))
+                    })
+                    .pull_to_push()
+                    .push_to(a_send),
+            );
+            builder.add_subgraph(
+                "echo",
+                b_recv.flatten().map(Some).pull_to_push().push_to(b_send),
+            );
+            builder.add_subgraph(
+                "log",
+                a_recv
+                    .flatten()
+                    .pull_to_push()
+                    .for_each(move |(from, message)| out_send.send((from, Some(message))).unwrap()),
+            );
+
+            builder.build().run_async().await.unwrap();
+        });
+    });
+
+    let (addr_b, _) = out_recv.recv().unwrap();
+    let mut received: Vec<_> = (0..3)
+        .map(|_| out_recv.recv_timeout(Duration::from_secs(5)).unwrap())
+        .map(|(from, message)| {
+            // The echoes come from B.
+            assert_eq!(addr_b, from);
+            message.unwrap()
+        })
+        .collect();
+    received.sort_by_key(|message| message.address);
+
+    let expected: Vec<_> = (0..3)
+        .map(|address| Message {
+            address,
+            batch: format!("ping {}", address).into_bytes().into(),
+        })
+        .collect();
+    assert_eq!(expected, received);
+}
+
+#[test]
+fn test_unix_socket() {
+    let path = std::env::temp_dir().join(format!("hydroflow-test-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let (out_send, out_recv) = channel();
+    let socket_path = path.clone();
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let mut builder = HydroflowBuilder::default();
+
+            let inbound = builder
+                .hydroflow
+                .inbound_unix_vertex::<(u64, String)>(&socket_path);
+            let inbound = builder.wrap_input(inbound);
+            let outbound = builder.hydroflow.outbound_unix_vertex::<(u64, String)>();
+            let outbound = builder.wrap_output(outbound);
+
+            builder.add_subgraph(
+                "send",
+                IterPullSurface::new(0..3)
+                    .map(move |i| Some((socket_path.clone(), (i, format!("hello {}", i)))))
+                    .pull_to_push()
+                    .push_to(outbound),
+            );
+            builder.add_subgraph(
+                "receive",
+                inbound
+                    .flatten()
+                    .pull_to_push()
+                    .for_each(move |msg| out_send.send(msg).unwrap()),
+            );
+
+            builder.build().run_async().await.unwrap();
+        });
+    });
+
+    let received: Vec<_> = (0..3)
+        .map(|_| out_recv.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect();
+    let _ = std::fs::remove_file(&path);
+
+    let expected: Vec<_> = (0..3).map(|i| (i, format!("hello {}", i))).collect();
+    assert_eq!(expected, received);
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_http_server() {
+    use hydroflow::scheduled::net::http::{self, Method, StatusCode};
+
+    let (addr_send, addr_recv) = channel();
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let mut builder = HydroflowBuilder::default();
+
+            let (addr, requests) = builder.add_http_server(([127, 0, 0, 1], 0).into());
+            addr_send.send(addr).unwrap();
+            let responder = builder.add_http_responder();
+
+            // Requests for any path other than `/greet` are dropped unanswered.
+            builder.add_subgraph(
+                "greet",
+                requests
+                    .flatten()
+                    .filter(|(request, _responder)| {
+                        request.method() == Method::POST && request.uri().path() == "/greet"
+                    })
+                    .map(|(request, responder)| {
+                        let name: String = http::json_body(&request).unwrap();
+                        let greeting = format!("hello {}", name);
+                        Some((
+                            responder,
+                            http::json_response(StatusCode::OK, &greeting).unwrap(),
+                        ))
+                    })
+                    .pull_to_push()
+                    .push_to(responder),
+            );
+
+            builder.build().run_async().await.unwrap();
+        });
+    });
+    let addr = addr_recv.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async move {
+        let client = hyper::Client::new();
+
+        let request = hyper::Request::post(format!("http://{}/greet", addr))
+            .body(hyper::Body::from(r#""world""#))
+            .unwrap();
+        let response = client.request(request).await.unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&br#""hello world""#[..], &*body);
+
+        let request = hyper::Request::get(format!("http://{}/missing", addr))
+            .body(hyper::Body::empty())
+            .unwrap();
+        let response = client.request(request).await.unwrap();
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+
+        // Too long, according to its Content-Length.
+        let request = hyper::Request::post(format!("http://{}/greet", addr))
+            .body(hyper::Body::from(vec![b' '; http::MAX_BODY_LEN + 1]))
+            .unwrap();
+        let response = client.request(request).await.unwrap();
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, response.status());
+
+        // Too long, but chunked so its length isn't known up front.
+        let (mut body_send, body) = hyper::Body::channel();
+        tokio::spawn(async move {
+            let chunk = bytes::Bytes::from(vec![b' '; 64 << 10]);
+            while body_send.send_data(chunk.clone()).await.is_ok() {}
+        });
+        let request = hyper::Request::post(format!("http://{}/greet", addr))
+            .body(body)
+            .unwrap();
+        let response = client.request(request).await.unwrap();
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, response.status());
+    });
+}
+
+#[test]
+fn test_exchange_loopback() {
+    // Like test_exchange, but all participants run in this thread and talk
+    // over a loopback network, so no ports are bound and the result doesn't
+    // depend on thread scheduling. Routes with exchange_by, and checks that
+    // each value ends up at the participant its partitioner picked.
+
+    const NUM_PARTICIPANTS: u64 = 3;
+
+    let data = [
+        (0, "zero", "zero"),
+        (1, "one", "une"),
+        (2, "two", "deux"),
+        (3, "three", "trois"),
+        (4, "four", "quatre"),
+        (5, "five", "cinq"),
+        (6, "six", "six"),
+        (7, "seven", "sept"),
+        (8, "eight", "huit"),
+        (9, "nine", "neuf"),
+        (10, "ten", "dix"),
+    ];
+
+    let network = LoopbackNetwork::new();
+    let english_address_book: HashMap<_, _> = (0..NUM_PARTICIPANTS)
+        .map(|id| (id, format!("english:{}", id)))
+        .collect();
+    let french_address_book: HashMap<_, _> = (0..NUM_PARTICIPANTS)
+        .map(|id| (id, format!("french:{}", id)))
+        .collect();
+
+    let (receipts_tx, receipts_rx) = std::sync::mpsc::channel();
+
+    let mut instances: Vec<_> = (0..NUM_PARTICIPANTS)
+        .map(|id| {
+            let mut builder = HydroflowBuilder::default();
+
+            // Participant `id` starts with the English words for the values
+            // equal to `id`, and the French words one participant over.
+            let english = data
+                .into_iter()
+                .filter(move |(v, _, _)| v % NUM_PARTICIPANTS == id)
+                .map(|(v, english, _)| (v, english.to_owned()));
+            let french = data
+                .into_iter()
+                .filter(move |(v, _, _)| (v + 1) % NUM_PARTICIPANTS == id)
+                .map(|(v, _, french)| (v, french.to_owned()));
+
+            let english_inbound = builder
+                .hydroflow
+                .inbound_loopback_vertex_at::<(u64, String)>(
+                    &network,
+                    english_address_book[&id].clone(),
+                );
+            let english_inbound = builder.wrap_input(english_inbound);
+            let english_outbound = builder
+                .hydroflow
+                .outbound_loopback_vertex::<(u64, String)>(&network);
+            let english_outbound = builder.wrap_output(english_outbound);
+
+            let french_inbound = builder
+                .hydroflow
+                .inbound_loopback_vertex_at::<(u64, String)>(
+                    &network,
+                    french_address_book[&id].clone(),
+                );
+            let french_inbound = builder.wrap_input(french_inbound);
+            let french_outbound = builder
+                .hydroflow
+                .outbound_loopback_vertex::<(u64, String)>(&network);
+            let french_outbound = builder.wrap_output(french_outbound);
+
+            let english = IterPullSurface::new(english).exchange_by(
+                &mut builder,
+                "english exchange",
+                english_address_book.clone(),
+                english_inbound.flatten(),
+                id,
+                |&k: &u64, n| k as usize % n,
+                english_outbound,
+            );
+            let french = IterPullSurface::new(french).exchange_by(
+                &mut builder,
+                "french exchange",
+                french_address_book.clone(),
+                french_inbound.flatten(),
+                id,
+                |&k: &u64, n| k as usize % n,
+                french_outbound,
+            );
+
+            let receipts_tx = receipts_tx.clone();
+            builder.add_subgraph(
+                "sink",
+                english
+                    .join(french)
+                    .pull_to_push()
+                    .for_each(move |(v, english, french)| {
+                        receipts_tx.send((id, v, english, french)).unwrap();
+                    }),
+            );
+
+            builder.build()
+        })
+        .collect();
+
+    // Every message sent in one round is received in the next.
+    for _ in 0..3 {
+        for instance in instances.iter_mut() {
+            instance.tick();
+        }
+    }
+
+    let mut out: Vec<_> = receipts_rx.try_iter().collect();
+    out.sort_unstable();
+
+    let mut expected: Vec<_> = data
+        .iter()
+        .map(|&(v, english, french)| {
+            (
+                v % NUM_PARTICIPANTS,
+                v,
+                english.to_owned(),
+                french.to_owned(),
+            )
+        })
+        .collect();
+    expected.sort_unstable();
+    assert_eq!(expected, out);
+}
+
+#[test]
+fn test_exchange_partition_out_of_range() {
+    let network = LoopbackNetwork::new();
+    let address_book: HashMap<_, _> = [(0, "only".to_owned())].into_iter().collect();
+
+    let mut builder = HydroflowBuilder::default();
+    let inbound = builder
+        .hydroflow
+        .inbound_loopback_vertex_at::<(u64, String)>(&network, "only".to_owned());
+    let inbound = builder.wrap_input(inbound);
+    let outbound = builder
+        .hydroflow
+        .outbound_loopback_vertex::<(u64, String)>(&network);
+    let outbound = builder.wrap_output(outbound);
+
+    // Partition 1 is past the only participant, so its record is dropped.
+    let (sg, out) = IterPullSurface::new(
+        [(0, "zero"), (1, "one")]
+            .into_iter()
+            .map(|(k, v)| (k, v.to_owned())),
+    )
+    .exchange_by(
+        &mut builder,
+        "exchange",
+        address_book,
+        inbound.flatten(),
+        0,
+        |&k: &u64, _| k as usize,
+        outbound,
+    )
+    .pull_to_push()
+    .collect_into();
+    builder.add_subgraph("sink", sg);
+
+    let mut hydroflow = builder.build();
+    hydroflow.tick();
+    assert_eq!(vec![(0, "zero".to_owned())], out.take());
+}
+
+#[test]
+#[should_panic(expected = "at least one participant")]
+fn test_exchange_empty_address_book() {
+    let network = LoopbackNetwork::new();
+    let mut builder = HydroflowBuilder::default();
+    let outbound = builder
+        .hydroflow
+        .outbound_loopback_vertex::<(u64, String)>(&network);
+    let outbound = builder.wrap_output(outbound);
+
+    IterPullSurface::new([(0, "zero".to_owned())].into_iter()).exchange(
+        &mut builder,
+        "exchange",
+        HashMap::new(),
+        IterPullSurface::new(std::iter::empty()),
+        0,
+        outbound,
+    );
+}
+
+#[test]
+fn test_batched_compressed_tcp() {
+    let (out_send, out_recv) = channel();
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let mut builder = HydroflowBuilder::default();
+
+            let (port, inbound) = builder
+                .hydroflow
+                .inbound_tcp_vertex_batched::<(u64, String)>()
+                .await;
+            let inbound = builder.wrap_input(inbound);
+
+            // Ten messages make two full batches, and a third sent once it
+            // has waited long enough.
+            let outbound = builder
+                .hydroflow
+                .outbound_tcp_vertex_batched::<(u64, String)>(BatchConfig {
+                    max_messages: 4,
+                    max_delay: Duration::from_millis(50),
+                    compression: Compression::Lz4,
+                    ..Default::default()
+                })
+                .await;
+            let outbound = builder.wrap_output(outbound);
+
+            let address = format!("localhost:{}", port);
+            builder.add_subgraph(
+                "send",
+                IterPullSurface::new(0..10)
+                    .map(move |i| Some((address.clone(), (i, "hello ".repeat(100)))))
+                    .pull_to_push()
+                    .push_to(outbound),
+            );
+            builder.add_subgraph(
+                "receive",
+                inbound
+                    .flatten()
+                    .pull_to_push()
+                    .for_each(move |msg| out_send.send(msg).unwrap()),
+            );
+
+            builder.build().run_async().await.unwrap();
+        });
+    });
+
+    let received: Vec<_> = (0..10)
+        .map(|_| out_recv.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect();
+    let expected: Vec<_> = (0..10).map(|i| (i, "hello ".repeat(100))).collect();
+    assert_eq!(expected, received);
+}
+
+/// Sends three messages over TLS to a server presenting a certificate for
+/// localhost, from a client trusting the CA in `client_ca`. Returns what the
+/// server receives.
+#[cfg(feature = "tls")]
+fn tls_send_receive(client_ca: &str) -> std::sync::mpsc::Receiver<(u64, String)> {
+    use hydroflow::scheduled::net::tls;
+
+    let certs = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/certs");
+    let server_config = tls::server_config(
+        tls::load_certs(format!("{}/localhost.pem", certs)).unwrap(),
+        tls::load_private_key(format!("{}/localhost.key", certs)).unwrap(),
+    )
+    .unwrap();
+    let client_config =
+        tls::client_config(&tls::load_certs(format!("{}/{}", certs, client_ca)).unwrap()).unwrap();
+
+    let (out_send, out_recv) = channel();
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let mut builder = HydroflowBuilder::default();
+
+            let (port, inbound) = builder
+                .hydroflow
+                .inbound_tls_vertex::<(u64, String)>(None, server_config)
+                .await;
+            let inbound = builder.wrap_input(inbound);
+            let outbound = builder
+                .hydroflow
+                .outbound_tls_vertex::<(u64, String)>(client_config)
+                .await;
+            let outbound = builder.wrap_output(outbound);
+
+            let address = format!("localhost:{}", port);
+            builder.add_subgraph(
+                "send",
+                IterPullSurface::new(0..3)
+                    .map(move |i| Some((address.clone(), (i, "secret".to_owned()))))
+                    .pull_to_push()
+                    .push_to(outbound),
+            );
+            builder.add_subgraph(
+                "receive",
+                inbound
+                    .flatten()
+                    .pull_to_push()
+                    .for_each(move |msg| out_send.send(msg).unwrap()),
+            );
+
+            builder.build().run_async().await.unwrap();
+        });
+    });
+    out_recv
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_tls() {
+    let out_recv = tls_send_receive("ca.pem");
+    let received: Vec<_> = (0..3)
+        .map(|_| out_recv.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect();
+    let expected: Vec<_> = (0..3).map(|i| (i, "secret".to_owned())).collect();
+    assert_eq!(expected, received);
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_tls_untrusted_server() {
+    // The client doesn't trust the CA which signed the server's certificate,
+    // so the handshake fails and nothing is sent.
+    let out_recv = tls_send_receive("other-ca.pem");
+    assert!(out_recv.recv_timeout(Duration::from_secs(1)).is_err());
+}
+
+#[test]
+fn test_tcp_mux() {
+    const GREETINGS: Channel<String> = Channel::new(0, "greeting");
+    const PAIRS: Channel<(u64, u64)> = Channel::new(1, "pair");
+
+    let (out_send, out_recv) = channel();
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let listener = TcpListener::bind("localhost:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let (client, (server, _)) =
+                tokio::try_join!(TcpStream::connect(addr), listener.accept()).unwrap();
+
+            let mut builder = HydroflowBuilder::default();
+
+            // The server echoes greetings and swaps pairs, and registers
+            // channel 2 with the wrong type tag.
+            let server_mux = builder.hydroflow.add_tcp_mux(server);
+            let (greetings_send, greetings_recv) =
+                server_mux.channel(&mut builder.hydroflow, GREETINGS);
+            let (pairs_send, pairs_recv) = server_mux.channel(&mut builder.hydroflow, PAIRS);
+            let (_, mismatched_recv) =
+                server_mux.channel(&mut builder.hydroflow, Channel::<String>::new(2, "string"));
+            builder.add_subgraph(
+                "echo greetings",
+                HandoffPullSurface::new(greetings_recv)
+                    .flatten()
+                    .map(|greeting| Some(greeting.to_uppercase()))
+                    .pull_to_push()
+                    .push_to(HandoffPushSurfaceReversed::new(greetings_send)),
+            );
+            builder.add_subgraph(
+                "swap pairs",
+                HandoffPullSurface::new(pairs_recv)
+                    .flatten()
+                    .map(|(a, b)| Some((b, a)))
+                    .pull_to_push()
+                    .push_to(HandoffPushSurfaceReversed::new(pairs_send)),
+            );
+            let mismatched_out = out_send.clone();
+            builder.add_subgraph(
+                "mismatched",
+                HandoffPullSurface::new(mismatched_recv)
+                    .flatten()
+                    .pull_to_push()
+                    .for_each(move |msg| {
+                        mismatched_out.send(format!("mismatched {}", msg)).unwrap()
+                    }),
+            );
+
+            let client_mux = builder.hydroflow.add_tcp_mux(client);
+            let (greetings_send, greetings_recv) =
+                client_mux.channel(&mut builder.hydroflow, GREETINGS);
+            let (pairs_send, pairs_recv) = client_mux.channel(&mut builder.hydroflow, PAIRS);
+            let (mismatched_send, _) =
+                client_mux.channel(&mut builder.hydroflow, Channel::<u64>::new(2, "u64"));
+
+            // Send on the mismatched channel first: frames are routed in
+            // order, so it is dropped before the other replies are sent.
+            let mismatched_input = builder.hydroflow.add_input("mismatched", mismatched_send);
+            mismatched_input.give(Some(7));
+            mismatched_input.flush();
+            builder.add_subgraph(
+                "greet",
+                IterPullSurface::new(["hello".to_owned()].into_iter())
+                    .map(Some)
+                    .pull_to_push()
+                    .push_to(HandoffPushSurfaceReversed::new(greetings_send)),
+            );
+            builder.add_subgraph(
+                "pair",
+                IterPullSurface::new([(1, 2)].into_iter())
+                    .map(Some)
+                    .pull_to_push()
+                    .push_to(HandoffPushSurfaceReversed::new(pairs_send)),
+            );
+
+            let greetings_out = out_send.clone();
+            builder.add_subgraph(
+                "greetings received",
+                HandoffPullSurface::new(greetings_recv)
+                    .flatten()
+                    .pull_to_push()
+                    .for_each(move |msg| greetings_out.send(msg).unwrap()),
+            );
+            builder.add_subgraph(
+                "pairs received",
+                HandoffPullSurface::new(pairs_recv)
+                    .flatten()
+                    .pull_to_push()
+                    .for_each(move |(a, b)| out_send.send(format!("({}, {})", a, b)).unwrap()),
+            );
+
+            builder.build().run_async().await.unwrap();
+        });
+    });
+
+    let mut received: Vec<_> = (0..2)
+        .map(|_| out_recv.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect();
+    received.sort();
+    assert_eq!(vec!["(2, 1)".to_owned(), "HELLO".to_owned()], received);
+    assert!(out_recv.try_recv().is_err());
+}
+
+type ClockRepr = MapUnionRepr<tag::HASH_MAP, usize, MaxRepr<u64>>;
+type DataRepr = MapUnionRepr<tag::HASH_MAP, u64, DomPairRepr<ClockRepr, MaxRepr<u64>>>;
+type Data = <DataRepr as LatticeRepr>::Repr;
+
+/// Runs a gossiping key-value store, in the style of `kvs`, on a simulated
+/// network with the given seed. Returns each replica's final state.
+fn run_gossip_simulation(seed: u64) -> (Vec<Data>, NetworkStats) {
+    const NUM_REPLICAS: usize = 3;
+
+    let mut simulation = Simulation::new(
+        seed,
+        FaultConfig {
+            min_delay: 0,
+            max_delay: 4,
+            drop_probability: 0.3,
+            duplicate_probability: 0.2,
+        },
+    );
+    let network = simulation.network().clone();
+    let addresses: Vec<_> = (0..NUM_REPLICAS)
+        .map(|id| format!("replica:{}", id))
+        .collect();
+
+    let mut states = Vec::new();
+    let mut gossip_inputs = Vec::new();
+    for id in 0..NUM_REPLICAS {
+        let mut builder = HydroflowBuilder::default();
+
+        // Every replica writes every key, with its own clock, so all the
+        // writes are concurrent.
+        let state: Rc<RefCell<Data>> = Rc::new(RefCell::new(
+            (0..5)
+                .map(|k| {
+                    let clock = [(id, k + 1)].into_iter().collect();
+                    (k, (clock, 10 * id as u64 + k))
+                })
+                .collect(),
+        ));
+
+        let inbound = builder
+            .hydroflow
+            .inbound_simulated_vertex::<Data>(&network, addresses[id].clone());
+        let inbound = builder.wrap_input(inbound);
+        let outbound = builder
+            .hydroflow
+            .outbound_simulated_vertex::<Data>(&network, addresses[id].clone());
+        let outbound = builder.wrap_output(outbound);
+
+        let merge_state = state.clone();
+        builder.add_subgraph(
+            "merge",
+            inbound.flatten().pull_to_push().for_each(move |delta| {
+                <DataRepr as Merge<DataRepr>>::merge(&mut merge_state.borrow_mut(), delta);
+            }),
+        );
+
+        // Each gossip round sends the whole state to every peer.
+        let (gossip_send, gossip) = builder.add_vec_input::<_, ()>("gossip");
+        let gossip_state = state.clone();
+        let peers: Vec<_> = addresses
+            .iter()
+            .filter(|&address| address != &addresses[id])
+            .cloned()
+            .collect();
+        builder.add_subgraph(
+            "gossip",
+            gossip
+                .flat_map(move |()| {
+                    let state = gossip_state.borrow().clone();
+                    peers
+                        .clone()
+                        .into_iter()
+                        .map(move |peer| Some((peer, state.clone())))
+                })
+                .pull_to_push()
+                .push_to(outbound),
+        );
+
+        simulation.add_instance(builder.build());
+        states.push(state);
+        gossip_inputs.push(gossip_send);
+    }
+
+    // Cut replica 0 off for the first half of the run.
+    network.partition([vec![addresses[0].clone()], addresses[1..].to_vec()]);
+    for round in 0..20 {
+        if round == 10 {
+            network.heal();
+        }
+        for input in gossip_inputs.iter() {
+            input.give(());
+            input.flush();
+        }
+        for _ in 0..5 {
+            simulation.step();
+        }
+    }
+    assert!(simulation.run_until_quiescent(100));
+
+    let states = states.iter().map(|state| state.borrow().clone()).collect();
+    (states, network.stats())
+}
+
+#[test]
+fn test_simulated_gossip_converges() {
+    let (states, stats) = run_gossip_simulation(42);
+
+    // The run exercised every kind of fault.
+    assert!(stats.dropped > 0);
+    assert!(stats.duplicated > 0);
+
+    // Concurrent writes are merged: the clocks are joined and the largest
+    // value wins.
+    let expected: Data = (0..5)
+        .map(|k| {
+            let clock = (0..3).map(|id| (id, k + 1)).collect();
+            (k, (clock, 20 + k))
+        })
+        .collect();
+    for state in states {
+        assert_eq!(expected, state);
+    }
+
+    // The same seed gives the same run.
+    assert_eq!(stats, run_gossip_simulation(42).1);
+}
+
+#[test]
+#[should_panic(expected = "drop_probability")]
+fn test_simulation_invalid_probability() {
+    Simulation::new(
+        0,
+        FaultConfig {
+            drop_probability: 1.5,
+            ..Default::default()
+        },
+    );
+}
+
 async fn open_connection(
     builder: &mut HydroflowBuilder,
 ) -> (
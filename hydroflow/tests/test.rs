@@ -423,3 +423,201 @@ fn test_input_channel() {
     }
     assert_eq!(result, expected);
 }
+
+#[test]
+fn test_file_lines() {
+    use hydroflow::builder::{prelude::*, HydroflowBuilder};
+
+    let dir = std::env::temp_dir();
+    let in_path = dir.join(format!("hydroflow-test-{}-in.txt", std::process::id()));
+    let out_path = dir.join(format!("hydroflow-test-{}-out.txt", std::process::id()));
+    // More lines than the source produces in one run.
+    let lines: Vec<_> = (0..3000).map(|i| i.to_string()).collect();
+    std::fs::write(&in_path, lines.join("\n")).unwrap();
+
+    let mut builder = HydroflowBuilder::default();
+    let input = builder.add_file_lines(&in_path).unwrap();
+    let output = builder.write_lines(&out_path).unwrap();
+    builder.add_subgraph(
+        "double",
+        input
+            .flatten()
+            .map(|line| Some(2 * line.parse::<u64>().unwrap()))
+            .pull_to_push()
+            .push_to(output),
+    );
+    builder.build().tick();
+
+    let written = std::fs::read_to_string(&out_path).unwrap();
+    let _ = std::fs::remove_file(&in_path);
+    let _ = std::fs::remove_file(&out_path);
+
+    let expected: String = (0..3000).map(|i| format!("{}\n", 2 * i)).collect();
+    assert_eq!(expected, written);
+}
+
+#[test]
+fn test_csv_source() {
+    use hydroflow::builder::{prelude::*, HydroflowBuilder};
+    use hydroflow::scheduled::io::csv;
+
+    let dir = std::env::temp_dir();
+    let path = dir.join(format!("hydroflow-test-{}.csv", std::process::id()));
+    let edges_path = dir.join(format!("hydroflow-test-{}-edges.txt", std::process::id()));
+    std::fs::write(&path, "name,count\napple,3\nbanana,oops\ncherry,5\n").unwrap();
+    std::fs::write(&edges_path, "1 2\n1 3\n2 4\n").unwrap();
+
+    let mut builder = HydroflowBuilder::default();
+    let input = builder.add_csv_source::<(String, u64)>(&path).unwrap();
+    let edges_input = builder
+        .add_csv_source_with_reader::<(u64, u64)>(
+            csv::ReaderBuilder::new().delimiter(b' ').has_headers(false),
+            &edges_path,
+        )
+        .unwrap();
+    let (sg, records) = input.flatten().pull_to_push().collect_into();
+    builder.add_subgraph("records", sg);
+    let (sg, edges) = edges_input.flatten().pull_to_push().collect_into();
+    builder.add_subgraph("edges", sg);
+    builder.build().tick();
+
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&edges_path);
+
+    // The record which doesn't deserialize is dropped.
+    assert_eq!(
+        vec![("apple".to_owned(), 3), ("cherry".to_owned(), 5)],
+        records.take()
+    );
+    assert_eq!(vec![(1, 2), (1, 3), (2, 4)], edges.take());
+}
+
+#[test]
+fn test_event_time_windows() {
+    use hydroflow::builder::{prelude::*, HydroflowBuilder};
+    use hydroflow::lang::event_time::{Event, Timestamp};
+
+    // (time, user) clicks and (time, user, item) purchases, slightly out of
+    // order.
+    let clicks = vec![
+        (1, "alice"),
+        (4, "bob"),
+        (2, "alice"),
+        (13, "bob"),
+        (25, "alice"),
+    ];
+    let purchases = vec![(3, "alice", "book"), (12, "bob", "pen"), (17, "bob", "cup")];
+
+    let mut builder = HydroflowBuilder::default();
+    let window_clicks = builder.add_input_from_stream_with_watermarks(
+        "window clicks",
+        futures::stream::iter(clicks.clone()),
+        |&(time, _user)| time,
+        5,
+    );
+    let join_clicks = builder.add_input_from_stream_with_watermarks(
+        "join clicks",
+        futures::stream::iter(clicks),
+        |&(time, _user)| time,
+        5,
+    );
+    let purchases = builder.add_input_from_stream_with_watermarks(
+        "purchases",
+        futures::stream::iter(purchases),
+        |&(time, _user, _item)| time,
+        5,
+    );
+
+    let (sg, windows) = window_clicks
+        .flatten()
+        .map(|event| event.map(|(_time, user)| user))
+        .window(10)
+        .pull_to_push()
+        .collect_into();
+    builder.add_subgraph("windows", sg);
+
+    let (sg, joined) = purchases
+        .flatten()
+        .map(|event| event.map(|(_time, user, item)| (user, item)))
+        .window_join(
+            join_clicks
+                .flatten()
+                .map(|event| event.map(|(time, user)| (user, time))),
+            10,
+        )
+        .pull_to_push()
+        .collect_into();
+    builder.add_subgraph("join", sg);
+
+    builder.build().tick();
+
+    assert_eq!(
+        vec![
+            Event::Item(0, vec!["alice", "bob", "alice"]),
+            // The click at 25 moves the watermark to 20, closing two windows.
+            Event::Item(10, vec!["bob"]),
+            Event::Watermark(20),
+            Event::Item(20, vec!["alice"]),
+            Event::Watermark(Timestamp::MAX),
+        ],
+        windows.take()
+    );
+
+    let joined = joined.take();
+    let mut items: Vec<_> = joined
+        .iter()
+        .filter_map(|event| match event {
+            Event::Item(time, item) => Some((*time, *item)),
+            Event::Watermark(_) => None,
+        })
+        .collect();
+    items.sort_unstable();
+    assert_eq!(
+        vec![
+            (0, ("alice", "book", 1)),
+            (0, ("alice", "book", 2)),
+            (10, ("bob", "cup", 13)),
+            (10, ("bob", "pen", 13)),
+        ],
+        items
+    );
+    assert_eq!(Some(&Event::Watermark(Timestamp::MAX)), joined.last());
+}
+
+#[test]
+fn test_causal_delivery() {
+    use hydroflow::builder::{prelude::*, HydroflowBuilder};
+    use hydroflow::lang::lattice::causal::{tick, VectorClock};
+
+    // Alice posts twice, and Bob replies after seeing her first post.
+    let mut alice = VectorClock::new();
+    tick(&mut alice, "alice");
+    let post = ("alice", alice.clone(), "post");
+    tick(&mut alice, "alice");
+    let edit = ("alice", alice, "edit");
+    let mut bob = post.1.clone();
+    tick(&mut bob, "bob");
+    let reply = ("bob", bob, "reply");
+
+    // Received out of order, with a duplicate.
+    let received = vec![reply, edit, post.clone(), post];
+
+    let mut builder = HydroflowBuilder::default();
+    let messages = builder.add_input_from_stream::<_, _, VecHandoff<_>, _>(
+        "messages",
+        futures::stream::iter(received.into_iter().map(Some)),
+    );
+    let (sg, delivered) = messages
+        .flatten()
+        .causal_delivery()
+        .map(|(_sender, _clock, msg)| msg)
+        .pull_to_push()
+        .collect_into();
+    builder.add_subgraph("causal delivery", sg);
+
+    builder.build().tick();
+
+    let delivered = delivered.take();
+    assert_eq!(3, delivered.len());
+    assert_eq!("post", delivered[0]);
+}
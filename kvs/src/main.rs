@@ -1,12 +1,15 @@
 #![feature(never_type)]
 
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{sync::Arc, time::Duration};
 
 use hydroflow::{
     lang::{
         collections::Single,
         lattice::{
-            dom_pair::DomPairRepr, map_union::MapUnionRepr, ord::MaxRepr, LatticeRepr, Merge,
+            causal::{CausalRepr, VectorClock, VectorClockRepr, VectorClockUpdateRepr},
+            map_union::MapUnionRepr,
+            ord::MaxRepr,
+            LatticeRepr, Merge,
         },
         tag,
     },
@@ -22,14 +25,14 @@ use hydroflow::{
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 struct ActorId(u64);
 
-type Timestamp = HashMap<usize, u64>;
+type Timestamp = VectorClock<usize>;
 
-type ClockRepr = MapUnionRepr<tag::HASH_MAP, usize, MaxRepr<u64>>;
-type ClockUpdateRepr = MapUnionRepr<tag::SINGLE, usize, MaxRepr<u64>>;
+type ClockRepr = VectorClockRepr<usize>;
+type ClockUpdateRepr = VectorClockUpdateRepr<usize>;
 
-type DataRepr<K, V> = MapUnionRepr<tag::HASH_MAP, K, DomPairRepr<ClockRepr, MaxRepr<V>>>;
-type BatchRepr<K, V> = MapUnionRepr<tag::VEC, K, DomPairRepr<ClockRepr, MaxRepr<V>>>;
-type UpdateRepr<K, V> = MapUnionRepr<tag::SINGLE, K, DomPairRepr<ClockRepr, MaxRepr<V>>>;
+type DataRepr<K, V> = MapUnionRepr<tag::HASH_MAP, K, CausalRepr<usize, MaxRepr<V>>>;
+type BatchRepr<K, V> = MapUnionRepr<tag::VEC, K, CausalRepr<usize, MaxRepr<V>>>;
+type UpdateRepr<K, V> = MapUnionRepr<tag::SINGLE, K, CausalRepr<usize, MaxRepr<V>>>;
 
 #[derive(Clone, Debug)]
 enum Message<K, V>